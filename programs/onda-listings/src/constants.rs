@@ -4,6 +4,11 @@ use solana_program::pubkey;
 pub const SECONDS_PER_DAY: i64 = 86_400;
 pub const SECONDS_PER_YEAR: i64 = 31_536_000;
 pub const LATE_REPAYMENT_FEE_BASIS_POINTS: u128 = 500;
-pub const SIGNER_PUBKEY: Pubkey = pubkey!("4RfijtGGJnnaLYYByWGTbkPrGgvmKeAP1bZBhwZApLPq");
-pub const SYSTEM_ACCOUNT: Pubkey = pubkey!("11111111111111111111111111111111");
-pub const ADMIN_PUBKEY: Pubkey = pubkey!("AH7F2EPHXWhfF5yc7xnv1zPbwz3YqD6CtAqbCyE9dy7r");
\ No newline at end of file
+pub const AUCTION_SETTLEMENT_GRACE_PERIOD: i64 = 86_400;
+pub const MAX_OFFER_COLLECTIONS: usize = 8;
+pub const MAX_COUNTERPARTIES: usize = 8;
+pub const MAX_RISK_COLLECTIONS: usize = 16;
+pub const MAX_RATE_POINTS: usize = 8;
+pub const MAX_DENYLIST_MINTS: usize = 256;
+pub const MAX_SIGNERS: usize = 4;
+pub const SYSTEM_ACCOUNT: Pubkey = pubkey!("11111111111111111111111111111111");
\ No newline at end of file
@@ -33,5 +33,55 @@ pub enum ErrorCodes {
     #[msg("Invalid escrow balance")]
     InvalidEscrowBalance,
     #[msg("Invalid token account delegate")]
-    InvalidDelegate
+    InvalidDelegate,
+    #[msg("Offer expired")]
+    OfferExpired,
+    #[msg("Listing expired")]
+    ListingExpired,
+    #[msg("Invalid merkle proof")]
+    InvalidProof,
+    #[msg("Too many collections")]
+    TooManyCollections,
+    #[msg("Too many counterparties")]
+    TooManyCounterparties,
+    #[msg("Counterparty not allowed")]
+    InvalidCounterparty,
+    #[msg("Exposure limit exceeded")]
+    ExposureLimitExceeded,
+    #[msg("Invalid rate curve")]
+    InvalidRateCurve,
+    #[msg("Invalid duration")]
+    InvalidDuration,
+    #[msg("Auction ended")]
+    AuctionEnded,
+    #[msg("Bid must be lower than the current rate")]
+    BidTooHigh,
+    #[msg("Terms do not match the expected terms")]
+    TermsMismatch,
+    #[msg("Invalid remaining accounts")]
+    InvalidRemainingAccounts,
+    #[msg("Invalid basis points")]
+    InvalidBasisPoints,
+    #[msg("Invalid referrer")]
+    InvalidReferrer,
+    #[msg("Terms are outside the collection's limits")]
+    TermsOutOfBounds,
+    #[msg("Mint is denylisted")]
+    MintDenied,
+    #[msg("Denylist full")]
+    DenylistFull,
+    #[msg("Invalid signers")]
+    InvalidSigners,
+    #[msg("Invalid attestation")]
+    InvalidAttestation,
+    #[msg("Attestation expired")]
+    AttestationExpired,
+    #[msg("Attestation must immediately precede the instruction")]
+    AttestationNotFound,
+    #[msg("Attestation must be a single signature with its key and message held in the same instruction")]
+    UnsupportedAttestationLayout,
+    #[msg("Attestation already used")]
+    AttestationUsed,
+    #[msg("Loans are disabled")]
+    LoansDisabled,
 }
\ No newline at end of file
@@ -23,42 +23,117 @@ pub mod onda_listings {
         amount: u64,
         basis_points: u16,
         duration: i64,
-        id: u8
+        expires_at: Option<i64>,
+        mint: Option<Pubkey>,
+        merkle_root: Option<[u8; 32]>,
+        collections: Vec<Pubkey>,
+        counterparties: Vec<Pubkey>,
+        rate_curve: Vec<RatePoint>,
     ) -> Result<()> {
-        handle_offer_loan(ctx, amount, basis_points, duration, id)
+        handle_offer_loan(ctx, amount, basis_points, duration, expires_at, mint, merkle_root, collections, counterparties, rate_curve)
     }
 
     pub fn take_loan_offer<'info>(
         ctx: Context<'_, '_, '_, 'info, TakeLoanOffer<'info>>,
-        id: u8,
+        id: u64,
+        proof: Vec<[u8; 32]>,
+        duration: Option<i64>,
+        expected_terms: LoanTerms,
+        collection_proof: Vec<[u8; 32]>,
     ) -> Result<()> {
-        handle_take_loan_offer(ctx, id)
+        handle_take_loan_offer(ctx, id, proof, duration, expected_terms, collection_proof)
+    }
+
+    pub fn update_loan_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateLoanOffer<'info>>,
+        id: u64,
+        amount: u64,
+        basis_points: u16,
+        duration: i64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        handle_update_loan_offer(ctx, id, amount, basis_points, duration, expires_at)
     }
 
     pub fn close_loan_offer<'info>(
         ctx: Context<'_, '_, '_, 'info, CloseLoanOffer<'info>>,
-        id: u8,
+        id: u64,
     ) -> Result<()> {
         handle_close_loan_offer(ctx, id)
     }
 
+    pub fn close_loan_offers<'info>(ctx: Context<'_, '_, '_, 'info, CloseLoanOffers<'info>>) -> Result<()> {
+        handle_close_loan_offers(ctx)
+    }
+
+    pub fn update_loan_offers<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateLoanOffers<'info>>,
+        terms: Vec<LoanTerms>,
+    ) -> Result<()> {
+        handle_update_loan_offers(ctx, terms)
+    }
+
+    pub fn close_expired_loan_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseExpiredLoanOffer<'info>>,
+        id: u64,
+    ) -> Result<()> {
+        handle_close_expired_loan_offer(ctx, id)
+    }
+
     pub fn ask_loan<'info>(
         ctx: Context<'_, '_, '_, 'info, AskLoan<'info>>,
         amount: u64,
         basis_points: u16,
-        duration: i64
+        duration: i64,
+        listing_expires_at: Option<i64>,
+        counterparties: Vec<Pubkey>,
+        auction_ends_at: Option<i64>,
+        collection_proof: Vec<[u8; 32]>,
     ) -> Result<()> {
-        handle_ask_loan(ctx, amount, basis_points, duration)
+        handle_ask_loan(ctx, amount, basis_points, duration, listing_expires_at, counterparties, auction_ends_at, collection_proof)
+    }
+
+    pub fn bid_loan<'info>(ctx: Context<'_, '_, '_, 'info, BidLoan<'info>>, basis_points: u16) -> Result<()> {
+        handle_bid_loan(ctx, basis_points)
     }
 
-    pub fn give_loan<'info>(ctx: Context<'_, '_, '_, 'info, GiveLoan<'info>>) -> Result<()> {
-        handle_give_loan(ctx)
+    pub fn settle_loan_auction<'info>(ctx: Context<'_, '_, '_, 'info, SettleLoanAuction<'info>>) -> Result<()> {
+        handle_settle_loan_auction(ctx)
+    }
+
+    pub fn cancel_loan_auction(ctx: Context<CancelLoanAuction>) -> Result<()> {
+        handle_cancel_loan_auction(ctx)
+    }
+
+    pub fn update_loan<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateLoan<'info>>,
+        amount: u64,
+        basis_points: u16,
+        duration: i64,
+        listing_expires_at: Option<i64>,
+    ) -> Result<()> {
+        handle_update_loan(ctx, amount, basis_points, duration, listing_expires_at)
+    }
+
+    pub fn give_loan<'info>(
+        ctx: Context<'_, '_, '_, 'info, GiveLoan<'info>>,
+        expected_terms: LoanTerms,
+    ) -> Result<()> {
+        handle_give_loan(ctx, expected_terms)
     }
 
     pub fn close_loan<'info>(ctx: Context<'_, '_, '_, 'info, CloseLoan<'info>>) -> Result<()> {
         handle_close_loan(ctx)
     }
 
+    pub fn close_expired_loan<'info>(ctx: Context<'_, '_, '_, 'info, CloseExpiredLoan<'info>>) -> Result<()> {
+        handle_close_expired_loan(ctx)
+    }
+
+    pub fn reclaim_loan<'info>(ctx: Context<'_, '_, '_, 'info, ReclaimLoan<'info>>) -> Result<()> {
+        handle_reclaim_loan(ctx)
+    }
+
     pub fn repay_loan<'info>(ctx: Context<'_, '_, '_, 'info, RepayLoan<'info>>, amount: u64) -> Result<()> {
         handle_repay_loan(ctx, amount)
     }
@@ -67,6 +142,14 @@ pub mod onda_listings {
         handle_repossess(ctx)
     }
 
+    pub fn migrate_loan(ctx: Context<MigrateLoan>, collection_proof: Vec<[u8; 32]>) -> Result<()> {
+        handle_migrate_loan(ctx, collection_proof)
+    }
+
+    pub fn migrate_loan_offer(ctx: Context<MigrateLoanOffer>, legacy_id: u8) -> Result<()> {
+        handle_migrate_loan_offer(ctx, legacy_id)
+    }
+
     // pub fn repossess_with_rental<'info>(ctx: Context<'_, '_, '_, 'info, RepossessWithRental<'info>>) -> Result<()> {
     //     handle_repossess_with_rental(ctx)
     // }
@@ -77,20 +160,52 @@ pub mod onda_listings {
         amount: u64,
         strike_price: u64,
         expiry: i64,
-        id: u8,
+        expires_at: Option<i64>,
+        mint: Option<Pubkey>,
+        merkle_root: Option<[u8; 32]>,
+        counterparties: Vec<Pubkey>,
     ) -> Result<()> {
-        handle_bid_call_option(ctx, amount, strike_price, expiry, id)
+        handle_bid_call_option(ctx, amount, strike_price, expiry, expires_at, mint, merkle_root, counterparties)
     }
 
-    pub fn close_call_option_bid(ctx: Context<CloseCallOptionBid>, id: u8) -> Result<()> {
+    pub fn update_call_option_bid(
+        ctx: Context<UpdateCallOptionBid>,
+        id: u64,
+        amount: u64,
+        strike_price: u64,
+        expiry: i64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        handle_update_call_option_bid(ctx, id, amount, strike_price, expiry, expires_at)
+    }
+
+    pub fn close_call_option_bid(ctx: Context<CloseCallOptionBid>, id: u64) -> Result<()> {
         handle_close_call_option_bid(ctx, id)
     }
 
+    pub fn close_call_option_bids<'info>(ctx: Context<'_, '_, '_, 'info, CloseCallOptionBids<'info>>) -> Result<()> {
+        handle_close_call_option_bids(ctx)
+    }
+
+    pub fn update_call_option_bids<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateCallOptionBids<'info>>,
+        terms: Vec<CallOptionTerms>,
+    ) -> Result<()> {
+        handle_update_call_option_bids(ctx, terms)
+    }
+
+    pub fn close_expired_call_option_bid(ctx: Context<CloseExpiredCallOptionBid>, id: u64) -> Result<()> {
+        handle_close_expired_call_option_bid(ctx, id)
+    }
+
     pub fn sell_call_option<'info>(
         ctx: Context<'_, '_, '_, 'info, SellCallOption<'info>>,
-        id: u8,
+        id: u64,
+        proof: Vec<[u8; 32]>,
+        expected_terms: CallOptionTerms,
+        collection_proof: Vec<[u8; 32]>,
     ) -> Result<()> {
-        handle_sell_call_option(ctx, id)
+        handle_sell_call_option(ctx, id, proof, expected_terms, collection_proof)
     }
 
     pub fn ask_call_option<'info>(
@@ -98,12 +213,28 @@ pub mod onda_listings {
         amount: u64,
         strike_price: u64,
         expiry: i64,
+        listing_expires_at: Option<i64>,
+        counterparties: Vec<Pubkey>,
+        collection_proof: Vec<[u8; 32]>,
     ) -> Result<()> {
-        handle_ask_call_option(ctx, amount, strike_price, expiry)
+        handle_ask_call_option(ctx, amount, strike_price, expiry, listing_expires_at, counterparties, collection_proof)
     }
 
-    pub fn buy_call_option<'info>(ctx: Context<'_, '_, '_, 'info, BuyCallOption<'info>>) -> Result<()> {
-        handle_buy_call_option(ctx)
+    pub fn update_call_option<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateCallOption<'info>>,
+        amount: u64,
+        strike_price: u64,
+        expiry: i64,
+        listing_expires_at: Option<i64>,
+    ) -> Result<()> {
+        handle_update_call_option(ctx, amount, strike_price, expiry, listing_expires_at)
+    }
+
+    pub fn buy_call_option<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyCallOption<'info>>,
+        expected_terms: CallOptionTerms,
+    ) -> Result<()> {
+        handle_buy_call_option(ctx, expected_terms)
     }
 
     pub fn exercise_call_option<'info>(ctx: Context<'_, '_, '_, 'info, ExerciseCallOption<'info>>) -> Result<()> {
@@ -118,6 +249,22 @@ pub mod onda_listings {
         handle_close_call_option(ctx)
     }
 
+    pub fn close_expired_call_option<'info>(ctx: Context<'_, '_, '_, 'info, CloseExpiredCallOption<'info>>) -> Result<()> {
+        handle_close_expired_call_option(ctx)
+    }
+
+    pub fn reclaim_call_option<'info>(ctx: Context<'_, '_, '_, 'info, ReclaimCallOption<'info>>) -> Result<()> {
+        handle_reclaim_call_option(ctx)
+    }
+
+    pub fn migrate_call_option(ctx: Context<MigrateCallOption>) -> Result<()> {
+        handle_migrate_call_option(ctx)
+    }
+
+    pub fn migrate_call_option_bid(ctx: Context<MigrateCallOptionBid>, legacy_id: u8) -> Result<()> {
+        handle_migrate_call_option_bid(ctx, legacy_id)
+    }
+
     // // Rentals
     // pub fn init_rental<'info>(
     //     ctx: Context<'_, '_, '_, 'info, InitRental<'info>>,
@@ -151,6 +298,15 @@ pub mod onda_listings {
         handle_init_collection(ctx, config)
     }
 
+    pub fn init_uncertified_collection(
+        ctx: Context<InitUncertifiedCollection>,
+        collection_type: CollectionType,
+        key: Pubkey,
+        config: Config,
+    ) -> Result<()> {
+        handle_init_uncertified_collection(ctx, collection_type, key, config)
+    }
+
     pub fn update_collection(ctx: Context<UpdateCollection>, config: Config) -> Result<()> {
         handle_update_collection(ctx, config)
     }
@@ -159,8 +315,88 @@ pub mod onda_listings {
         handle_close_collection(ctx)
     }
 
+    pub fn migrate_collection(ctx: Context<MigrateCollection>) -> Result<()> {
+        handle_migrate_collection(ctx)
+    }
+
+    // Denylist
+    pub fn init_denylist(ctx: Context<InitDenylist>) -> Result<()> {
+        handle_init_denylist(ctx)
+    }
+
+    pub fn update_denylist(ctx: Context<UpdateDenylist>, deny: Vec<Pubkey>, allow: Vec<Pubkey>) -> Result<()> {
+        handle_update_denylist(ctx, deny, allow)
+    }
+
+    pub fn close_denylist(ctx: Context<CloseDenylist>) -> Result<()> {
+        handle_close_denylist(ctx)
+    }
+
+    // Lender risk
+    pub fn init_lender_risk(ctx: Context<InitLenderRisk>, limits: RiskLimits) -> Result<()> {
+        handle_init_lender_risk(ctx, limits)
+    }
+
+    pub fn update_lender_risk(ctx: Context<UpdateLenderRisk>, limits: RiskLimits) -> Result<()> {
+        handle_update_lender_risk(ctx, limits)
+    }
+
+    pub fn close_lender_risk(ctx: Context<CloseLenderRisk>) -> Result<()> {
+        handle_close_lender_risk(ctx)
+    }
+
+    // Referrers
+    pub fn init_referrer_stats(ctx: Context<InitReferrerStats>) -> Result<()> {
+        handle_init_referrer_stats(ctx)
+    }
+
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        handle_claim_referral_fees(ctx)
+    }
+
+    // Global config
+    pub fn init_global_config(
+        ctx: Context<InitGlobalConfig>,
+        admin: Pubkey,
+        signers: Vec<Pubkey>,
+        fees: ProtocolFees,
+        flags: GlobalFlags,
+    ) -> Result<()> {
+        handle_init_global_config(ctx, admin, signers, fees, flags)
+    }
+
+    pub fn update_global_config(
+        ctx: Context<UpdateGlobalConfig>,
+        signers: Vec<Pubkey>,
+        fees: ProtocolFees,
+        flags: GlobalFlags,
+    ) -> Result<()> {
+        handle_update_global_config(ctx, signers, fees, flags)
+    }
+
+    pub fn set_global_config_admin(ctx: Context<SetGlobalConfigAdmin>) -> Result<()> {
+        handle_set_global_config_admin(ctx)
+    }
+
+    // Treasury
+    pub fn init_treasury(ctx: Context<InitTreasury>) -> Result<()> {
+        handle_init_treasury(ctx)
+    }
+
+    pub fn withdraw_from_treasury(ctx: Context<WithdrawFromTreasury>, amount: u64) -> Result<()> {
+        handle_withdraw_from_treasury(ctx, amount)
+    }
+
     // Common
     pub fn claim(ctx: Context<Claim>) -> Result<()> {
         handle_claim(ctx)
     }
+
+    pub fn migrate_token_manager(ctx: Context<MigrateTokenManager>) -> Result<()> {
+        handle_migrate_token_manager(ctx)
+    }
+
+    pub fn close_attestation_receipt(ctx: Context<CloseAttestationReceipt>) -> Result<()> {
+        handle_close_attestation_receipt(ctx)
+    }
 }
\ No newline at end of file
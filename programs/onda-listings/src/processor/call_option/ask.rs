@@ -1,6 +1,6 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{CallOption, Collection, TokenManager};
+use crate::state::{CallOption, Collection, TokenManager, GlobalConfig};
 use crate::error::{ErrorCodes};
 use crate::utils::*;
 use crate::constants::*;
@@ -9,29 +9,27 @@ use crate::constants::*;
 #[instruction(amount: u64, strike_price: u64, expiry: i64)]
 pub struct AskCallOption<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
     )]
-    pub signer: Signer<'info>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    /// Only required with an attestation
+    #[account(mut)]
+    /// CHECK: derived and created in assert_attested
+    pub attestation_receipt: Option<UncheckedAccount<'info>>,
     #[account(mut)]
     pub seller: Signer<'info>,
     #[account(
         mut,
         constraint = deposit_token_account.amount == 1,
+        constraint = deposit_token_account.owner == seller.key(),
         constraint = deposit_token_account.mint == mint.key(),
     )]
     pub deposit_token_account: Box<Account<'info, TokenAccount>>,
-    #[account(
-        init,
-        payer = seller,
-        seeds = [
-            CallOption::PREFIX,
-            mint.key().as_ref(),
-            seller.key().as_ref(),
-        ],
-        space = CallOption::space(),
-        bump,
-    )]
-    pub call_option: Box<Account<'info, CallOption>>, 
     #[account(
         init_if_needed,
         payer = seller,
@@ -47,15 +45,30 @@ pub struct AskCallOption<'info> {
         ) @ ErrorCodes::Unauthorized,
     )]   
     pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(
+        init,
+        payer = seller,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+            &token_manager.next_sequence().to_le_bytes(),
+        ],
+        space = CallOption::space(),
+        bump,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>, 
     #[account(
         seeds = [
             Collection::PREFIX,
             collection.mint.as_ref(),
         ],
         bump,
-        constraint = collection.config.option_enabled == true
+        constraint = collection.config.option_enabled && global_config.flags.options_enabled
     )]
     pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: derived and deserialized if initialized
+    pub denylist: UncheckedAccount<'info>,
     #[account(constraint = mint.supply == 1)]
     pub mint: Box<Account<'info, Mint>>,
     #[account(mut)]
@@ -85,8 +98,21 @@ pub fn handle_ask_call_option(
   ctx: Context<AskCallOption>,
   amount: u64,
   strike_price: u64,
-  expiry: i64
+  expiry: i64,
+  listing_expires_at: Option<i64>,
+  counterparties: Vec<Pubkey>,
+  collection_proof: Vec<[u8; 32]>,
 ) -> Result<()> {
+    assert_attested(
+        &ctx.accounts.global_config,
+        &ctx.accounts.signer,
+        &ctx.accounts.attestation_receipt,
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.sysvar_instructions,
+        ctx.accounts.collection.config.permissionless,
+    )?;
+
     let call_option = &mut ctx.accounts.call_option;
     let seller = &ctx.accounts.seller;
     let token_manager = &mut ctx.accounts.token_manager;
@@ -106,31 +132,63 @@ pub fn handle_ask_call_option(
     assert_collection_valid(
         &ctx.accounts.metadata,
         ctx.accounts.mint.key(),
-        ctx.accounts.collection.key(),
-        ctx.program_id.clone(),
+        &ctx.accounts.collection,
+        &collection_proof,
+    )?;
+    assert_mint_not_denied(
+        &ctx.accounts.denylist,
+        &ctx.accounts.collection.key(),
+        &ctx.accounts.mint.key(),
+        ctx.program_id,
     )?;
 
+    collection.config.assert_option_expiry_valid(expiry, unix_timestamp)?;
+
     if unix_timestamp > expiry {
         return Err(ErrorCodes::InvalidExpiry.into())
     }
 
+    if let Some(listing_expires_at) = listing_expires_at {
+        require_gt!(listing_expires_at, unix_timestamp, ErrorCodes::InvalidExpiry);
+    }
+
+    require_gte!(MAX_COUNTERPARTIES, counterparties.len(), ErrorCodes::TooManyCounterparties);
+
     require_eq!(token_manager.accounts.loan, false, ErrorCodes::InvalidState);
+    // Positions are no longer unique per PDA, so only one may be open on a mint at a time
+    require_eq!(token_manager.accounts.call_option, false, ErrorCodes::InvalidState);
 
     // Init
     call_option.seller = ctx.accounts.seller.key();
     call_option.mint = ctx.accounts.mint.key();
     call_option.bump = *ctx.bumps.get("call_option").unwrap();
     //
+    let creator_basis_points = get_creator_basis_points(
+        &collection.config,
+        collection.config.option_basis_points,
+        &metadata.to_account_info(),
+    )?;
     CallOption::init_ask_state(
         call_option,
         amount,
-        collection.config.option_basis_points,
+        creator_basis_points,
         strike_price,
         expiry
     )?;
+    call_option.exercise_basis_points = get_exercise_basis_points(
+        &collection.config,
+        &metadata.to_account_info(),
+    )?;
+    call_option.verified_creators_only = collection.config.verified_creators_only;
+    call_option.payout = collection.config.royalty_payout();
+    call_option.permissionless = collection.config.permissionless;
+    call_option.listing_expires_at = listing_expires_at;
+    call_option.counterparties = counterparties;
     //
     token_manager.authority = Some(seller.key());
     token_manager.accounts.call_option = true;
+    token_manager.sequence = token_manager.next_sequence();
+    call_option.sequence = token_manager.sequence;
     token_manager.bump = *ctx.bumps.get("token_manager").unwrap();
 
     // Freeze deposit token account
@@ -138,6 +196,31 @@ pub fn handle_ask_call_option(
         if deposit_token_account.delegate.unwrap() != token_manager.key() {
             return err!(ErrorCodes::InvalidState);
         }
+
+        // An expired listing may have been thawed without revoking the delegate
+        if !deposit_token_account.is_frozen() {
+            handle_freeze(
+                token_manager,
+                seller.to_account_info(),
+                seller.to_account_info(),
+                deposit_token_account.to_account_info(),
+                match token_record {
+                    Some(token_record) => Some(token_record.to_account_info()),
+                    None => None,
+                },
+                mint.to_account_info(),
+                metadata.to_account_info(),
+                edition.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                sysvar_instructions.to_account_info(),
+                authorization_rules_program.to_account_info(),
+                match authorization_rules {
+                    Some(authorization_rules) => Some(authorization_rules.to_account_info()),
+                    None => None,
+                }
+            )?;
+        }
     } else {
         handle_delegate_and_freeze(
             token_manager,
@@ -0,0 +1,158 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        program::{invoke_signed},
+        system_instruction::{transfer}
+    },
+    AccountsClose
+};
+use crate::state::{CallOptionBid, Collection, CallOptionTerms, GlobalConfig};
+use crate::error::{ErrorCodes};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct CloseCallOptionBids<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    // remaining accounts: [call_option_bid, escrow_payment_account, collection] triples
+}
+
+#[derive(Accounts)]
+pub struct UpdateCallOptionBids<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    // remaining accounts: [call_option_bid, escrow_payment_account, collection] triples
+}
+
+fn load_call_option_bid<'info>(
+    accounts: &[AccountInfo<'info>],
+    buyer: &Pubkey,
+    signer: &Option<Signer<'info>>,
+    program_id: &Pubkey,
+) -> Result<(Account<'info, CallOptionBid>, Account<'info, Collection>)> {
+    let call_option_bid = Account::<CallOptionBid>::try_from(&accounts[0])?;
+    let collection = Account::<Collection>::try_from(&accounts[2])?;
+
+    require_keys_eq!(call_option_bid.buyer, *buyer, ErrorCodes::Unauthorized);
+    require_keys_eq!(call_option_bid.collection, collection.key(), ErrorCodes::InvalidCollection);
+
+    assert_batch_accounts_valid(
+        accounts,
+        &collection,
+        signer,
+        &[
+            CallOptionBid::PREFIX,
+            collection.mint.as_ref(),
+            buyer.as_ref(),
+            &call_option_bid.id.to_le_bytes(),
+            &[call_option_bid.bump],
+        ],
+        CallOptionBid::VAULT_PREFIX,
+        call_option_bid.escrow_bump,
+        program_id,
+    )?;
+
+    Ok((call_option_bid, collection))
+}
+
+pub fn handle_close_call_option_bids<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseCallOptionBids<'info>>,
+) -> Result<()> {
+    let buyer = &ctx.accounts.buyer;
+
+    require!(ctx.remaining_accounts.len() % 3 == 0, ErrorCodes::InvalidRemainingAccounts);
+
+    for accounts in ctx.remaining_accounts.chunks(3) {
+        let escrow_payment_account = &accounts[1];
+        let (call_option_bid, _) = load_call_option_bid(accounts, &buyer.key(), &ctx.accounts.signer, ctx.program_id)?;
+
+        let call_option_bid_pubkey = call_option_bid.key();
+        let signer_bump = &[call_option_bid.escrow_bump];
+        let signer_seeds = &[&[
+            CallOptionBid::VAULT_PREFIX,
+            call_option_bid_pubkey.as_ref(),
+            signer_bump
+        ][..]];
+
+        // Return the full vault balance, including its rent, to the buyer
+        invoke_signed(
+            &transfer(
+                &escrow_payment_account.key(),
+                &buyer.key(),
+                escrow_payment_account.lamports(),
+            ),
+            &[
+                escrow_payment_account.clone(),
+                buyer.to_account_info(),
+            ],
+            signer_seeds
+        )?;
+
+        call_option_bid.close(buyer.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+pub fn handle_update_call_option_bids<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateCallOptionBids<'info>>,
+    terms: Vec<CallOptionTerms>,
+) -> Result<()> {
+    let buyer = &ctx.accounts.buyer;
+    let unix_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(ctx.remaining_accounts.len() % 3 == 0, ErrorCodes::InvalidRemainingAccounts);
+    require_eq!(ctx.remaining_accounts.len() / 3, terms.len(), ErrorCodes::InvalidRemainingAccounts);
+
+    for (accounts, terms) in ctx.remaining_accounts.chunks(3).zip(terms.iter()) {
+        let escrow_payment_account = &accounts[1];
+        let (mut call_option_bid, collection) = load_call_option_bid(accounts, &buyer.key(), &ctx.accounts.signer, ctx.program_id)?;
+
+        CallOptionBid::assert_terms_valid(&collection.config, terms.expiry, unix_timestamp)?;
+
+        let call_option_bid_pubkey = call_option_bid.key();
+        let signer_bump = &[call_option_bid.escrow_bump];
+        let signer_seeds = &[&[
+            CallOptionBid::VAULT_PREFIX,
+            call_option_bid_pubkey.as_ref(),
+            signer_bump
+        ][..]];
+
+        adjust_escrow_balance(
+            buyer.to_account_info(),
+            escrow_payment_account.clone(),
+            signer_seeds,
+            call_option_bid.amount,
+            terms.amount,
+        )?;
+
+        call_option_bid.amount = terms.amount;
+        call_option_bid.strike_price = terms.strike_price;
+        call_option_bid.expiry = terms.expiry;
+        call_option_bid.exit(ctx.program_id)?;
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,23 +1,45 @@
 use anchor_lang::{system_program, prelude::*};
-use crate::state::{CallOptionBid, Collection};
+use crate::state::{CallOptionBid, OfferCounter, Collection, GlobalConfig};
+use crate::error::*;
+use crate::utils::*;
 use crate::constants::*;
 
 #[derive(Accounts)]
-#[instruction(amount: u64, strike_price: u64, expiry: i64, id: u8)]
+#[instruction(amount: u64, strike_price: u64, expiry: i64)]
 pub struct BidCallOption<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
     )]
-    pub signer: Signer<'info>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    /// Only required with an attestation
+    #[account(mut)]
+    /// CHECK: derived and created in assert_attested
+    pub attestation_receipt: Option<UncheckedAccount<'info>>,
     #[account(mut)]
     pub buyer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [
+            OfferCounter::PREFIX,
+            buyer.key().as_ref(),
+        ],
+        payer = buyer,
+        space = OfferCounter::space(),
+        bump,
+    )]
+    pub offer_counter: Box<Account<'info, OfferCounter>>,
     #[account(
         init,
         seeds = [
             CallOptionBid::PREFIX,
             collection.mint.as_ref(),
             buyer.key().as_ref(),
-            &[id],
+            &offer_counter.next_id.to_le_bytes(),
         ],
         payer = buyer,
         space = CallOptionBid::space(),
@@ -43,12 +65,15 @@ pub struct BidCallOption<'info> {
             collection.mint.as_ref(),
         ],
         bump,
-        constraint = collection.config.option_enabled == true
+        constraint = collection.config.option_enabled && global_config.flags.options_enabled
     )]
     pub collection: Box<Account<'info, Collection>>,
     /// Misc
+    /// CHECK: address checked when the attestation is loaded
+    pub sysvar_instructions: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+    pub clock: Sysvar<'info, Clock>,
 }
 
 pub fn handle_bid_call_option(
@@ -56,20 +81,50 @@ pub fn handle_bid_call_option(
   amount: u64,
   strike_price: u64,
   expiry: i64,
-  offer_id: u8,
+  expires_at: Option<i64>,
+  mint: Option<Pubkey>,
+  merkle_root: Option<[u8; 32]>,
+  counterparties: Vec<Pubkey>,
 ) -> Result<()> {
+    assert_attested(
+        &ctx.accounts.global_config,
+        &ctx.accounts.signer,
+        &ctx.accounts.attestation_receipt,
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.sysvar_instructions,
+        ctx.accounts.collection.config.permissionless,
+    )?;
+
     let bid = &mut ctx.accounts.call_option_bid;
+    let offer_counter = &mut ctx.accounts.offer_counter;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if let Some(expires_at) = expires_at {
+        require_gt!(expires_at, unix_timestamp, ErrorCodes::InvalidExpiry);
+    }
+
+    require_gte!(MAX_COUNTERPARTIES, counterparties.len(), ErrorCodes::TooManyCounterparties);
+    CallOptionBid::assert_terms_valid(&ctx.accounts.collection.config, expiry, unix_timestamp)?;
 
     // Init
     bid.collection = ctx.accounts.collection.key();
     bid.bump = *ctx.bumps.get("call_option_bid").unwrap();
     bid.escrow_bump = *ctx.bumps.get("escrow_payment_account").unwrap();
     //
-    bid.id = offer_id;
+    bid.id = offer_counter.next_id;
     bid.buyer = ctx.accounts.buyer.key();
     bid.amount = amount;
     bid.strike_price = strike_price;
     bid.expiry = expiry;
+    bid.expires_at = expires_at;
+    bid.mint = mint;
+    bid.merkle_root = merkle_root;
+    bid.counterparties = counterparties;
+    //
+    offer_counter.owner = ctx.accounts.buyer.key();
+    offer_counter.bump = *ctx.bumps.get("offer_counter").unwrap();
+    offer_counter.next_id = offer_counter.next_id.checked_add(1).ok_or(ErrorCodes::NumericalOverflow)?;
 
     // Transfer amount
     anchor_lang::solana_program::program::invoke(
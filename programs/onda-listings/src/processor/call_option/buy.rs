@@ -1,16 +1,24 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token};
-use crate::state::{CallOption, CallOptionState, Collection, TokenManager};
+use crate::state::{CallOption, CallOptionState, CallOptionTerms, Collection, ReferrerStats, TokenManager, Treasury, GlobalConfig};
 use crate::error::{ErrorCodes};
-use crate::constants::*;
 use crate::utils::*;
 
 #[derive(Accounts)]
 pub struct BuyCallOption<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
     )]
-    pub signer: Signer<'info>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    /// Only required with an attestation
+    #[account(mut)]
+    /// CHECK: derived and created in assert_attested
+    pub attestation_receipt: Option<UncheckedAccount<'info>>,
     /// CHECK: contrained on listing_account
     #[account(mut)]
     pub seller: AccountInfo<'info>,
@@ -23,12 +31,14 @@ pub struct BuyCallOption<'info> {
             CallOption::PREFIX,
             mint.key().as_ref(),
             seller.key().as_ref(),
+            &call_option.sequence.to_le_bytes(),
         ],
         bump,
         has_one = seller,
         has_one = mint,
         constraint = call_option.seller != buyer.key(),
         constraint = call_option.state == CallOptionState::Listed,
+        constraint = call_option.allows_counterparty(&buyer.key()) @ ErrorCodes::InvalidCounterparty,
     )]
     pub call_option: Box<Account<'info, CallOption>>,   
     #[account(
@@ -47,9 +57,29 @@ pub struct BuyCallOption<'info> {
             collection.mint.as_ref(),
         ],
         bump,
-        constraint = collection.config.option_enabled == true
+        constraint = collection.config.option_enabled && global_config.flags.options_enabled
     )]
     pub collection: Box<Account<'info, Collection>>,
+    #[account(
+        mut,
+        seeds = [
+            Treasury::PREFIX,
+        ],
+        bump,
+    )]
+    /// CHECK: deserialized if initialized
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: constrained on referrer_stats
+    pub referrer: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [
+            ReferrerStats::PREFIX,
+            referrer_stats.referrer.as_ref(),
+        ],
+        bump,
+    )]
+    pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: deserialized and checked
     pub metadata: UncheckedAccount<'info>,
@@ -58,35 +88,86 @@ pub struct BuyCallOption<'info> {
     /// CHECK: validated in cpi
     pub metadata_program: UncheckedAccount<'info>, 
     /// Misc
+    /// CHECK: address checked when the attestation is loaded
+    pub sysvar_instructions: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub clock: Sysvar<'info, Clock>,
 }
 
-pub fn handle_buy_call_option<'info>(ctx: Context<'_, '_, '_, 'info, BuyCallOption<'info>>) -> Result<()> {
+pub fn handle_buy_call_option<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyCallOption<'info>>,
+    expected_terms: CallOptionTerms,
+) -> Result<()> {
+    assert_attested(
+        &ctx.accounts.global_config,
+        &ctx.accounts.signer,
+        &ctx.accounts.attestation_receipt,
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.sysvar_instructions,
+        ctx.accounts.collection.config.permissionless,
+    )?;
+
     let call_option = &mut ctx.accounts.call_option;
     let collection = &ctx.accounts.collection;
     let remaining_accounts = &mut ctx.remaining_accounts.iter();
 
+    require!(!call_option.is_listing_expired(ctx.accounts.clock.unix_timestamp), ErrorCodes::ListingExpired);
+    expected_terms.assert_matches(call_option.amount, call_option.strike_price, call_option.expiry)?;
+    // Updated asks are only checked against the collection's limits when bought
+    collection.config.assert_option_expiry_valid(call_option.expiry, ctx.accounts.clock.unix_timestamp)?;
+
     call_option.buyer = Some(ctx.accounts.buyer.key());
     CallOption::set_active(call_option, ctx.accounts.clock.unix_timestamp)?;
 
-    let fee_basis_points = collection.config.option_basis_points;
     let remaining_amount = pay_creator_fees(
         call_option.amount,
-        fee_basis_points, 
+        call_option.creator_basis_points,
+        call_option.verified_creators_only,
+        call_option.payout,
         &ctx.accounts.mint.to_account_info(),
         &ctx.accounts.metadata.to_account_info(),
         &mut ctx.accounts.buyer.to_account_info(),
         remaining_accounts
     )?;
 
+    // The protocol takes its share of the premium
+    let protocol_fee = get_protocol_fee(
+        &ctx.accounts.treasury,
+        &ctx.accounts.global_config,
+        &collection.config,
+        call_option.amount
+    )?;
+
+    let referral_fee = get_referral_fee(
+        &ctx.accounts.treasury,
+        &ctx.accounts.global_config,
+        protocol_fee,
+        &ctx.accounts.referrer,
+        &mut ctx.accounts.referrer_stats,
+    )?;
+
+    if let Some(referrer_stats) = &ctx.accounts.referrer_stats {
+        pay_protocol_fee(
+            referral_fee,
+            ctx.accounts.buyer.to_account_info(),
+            referrer_stats.to_account_info(),
+        )?;
+    }
+
+    pay_protocol_fee(
+        protocol_fee - referral_fee,
+        ctx.accounts.buyer.to_account_info(),
+        ctx.accounts.treasury.to_account_info(),
+    )?;
+
     // Transfer option cost
     anchor_lang::solana_program::program::invoke(
         &anchor_lang::solana_program::system_instruction::transfer(
             &call_option.buyer.unwrap(),
             &call_option.seller,
-            remaining_amount,
+            remaining_amount.checked_sub(protocol_fee).ok_or(ErrorCodes::NumericalOverflow)?,
         ),
         &[
             ctx.accounts.seller.to_account_info(),
@@ -5,21 +5,15 @@ use {
             program::{invoke_signed},
             system_instruction::{transfer}
         },
-        AccountsClose
     },
     anchor_spl::token::{Mint, Token, TokenAccount}
 };
-use crate::state::{CallOption, CallOptionBid, CallOptionState, Collection, TokenManager};
+use crate::state::{CallOption, CallOptionBid, CallOptionState, Collection, TokenManager, GlobalConfig};
 use crate::error::{ErrorCodes};
 use crate::utils::*;
-use crate::constants::*;
 
 #[derive(Accounts)]
 pub struct CloseCallOption<'info> {
-    #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
-    )]
-    pub signer: Signer<'info>,
     /// CHECK: contrained on listing_account
     #[account(mut)]
     pub seller: Signer<'info>,
@@ -29,11 +23,12 @@ pub struct CloseCallOption<'info> {
             CallOption::PREFIX,
             mint.key().as_ref(),
             seller.key().as_ref(),
+            &call_option.sequence.to_le_bytes(),
         ],
         bump,
         has_one = seller,
         has_one = mint,
-        close = seller
+        close = seller,
     )]
     pub call_option: Account<'info, CallOption>,
     #[account(
@@ -126,7 +121,7 @@ pub fn handle_close_call_option(ctx: Context<CloseCallOption>) -> Result<()> {
                 },
             )?;
         
-            token_manager.close(seller.to_account_info())?;    
+            token_manager.release();
         } else {
             token_manager.accounts.loan = false;
         }   
@@ -136,12 +131,17 @@ pub fn handle_close_call_option(ctx: Context<CloseCallOption>) -> Result<()> {
 }
 
 #[derive(Accounts)]
-#[instruction(id: u8)]
+#[instruction(id: u64)]
 pub struct CloseCallOptionBid<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
     )]
-    pub signer: Signer<'info>,
+    pub signer: Option<Signer<'info>>,
     #[account(mut)]
     pub buyer: Signer<'info>,
     #[account(
@@ -150,7 +150,7 @@ pub struct CloseCallOptionBid<'info> {
             CallOptionBid::PREFIX,
             collection.mint.as_ref(),
             buyer.key().as_ref(),
-            &[id],
+            &id.to_le_bytes(),
         ],
         close = buyer,
         bump,
@@ -172,6 +172,7 @@ pub struct CloseCallOptionBid<'info> {
             collection.mint.as_ref(),
         ],
         bump,
+        constraint = signer.is_some() || collection.config.permissionless @ ErrorCodes::Unauthorized,
     )]
     pub collection: Box<Account<'info, Collection>>,
     /// Misc
@@ -179,7 +180,7 @@ pub struct CloseCallOptionBid<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn handle_close_call_option_bid(ctx: Context<CloseCallOptionBid>, _id: u8) -> Result<()> {
+pub fn handle_close_call_option_bid(ctx: Context<CloseCallOptionBid>, _id: u64) -> Result<()> {
     let call_option_bid = &ctx.accounts.call_option_bid;
     let escrow_payment_account = &ctx.accounts.escrow_payment_account;
 
@@ -191,11 +192,12 @@ pub fn handle_close_call_option_bid(ctx: Context<CloseCallOptionBid>, _id: u8) -
         signer_bump
     ][..]];
 
+    // Return the full vault balance, including its rent, to the buyer
     invoke_signed(
         &transfer(
             &escrow_payment_account.key(),
             &call_option_bid.buyer,
-            call_option_bid.amount,
+            escrow_payment_account.lamports(),
         ),
         &[
             escrow_payment_account.to_account_info(),
@@ -205,4 +207,216 @@ pub fn handle_close_call_option_bid(ctx: Context<CloseCallOptionBid>, _id: u8) -
     )?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CloseExpiredCallOptionBid<'info> {
+    /// CHECK: constrained on call_option_bid
+    #[account(mut)]
+    pub buyer: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOptionBid::PREFIX,
+            collection.mint.as_ref(),
+            buyer.key().as_ref(),
+            &id.to_le_bytes(),
+        ],
+        bump,
+        has_one = buyer,
+        close = buyer,
+    )]
+    pub call_option_bid: Box<Account<'info, CallOptionBid>>,
+    #[account(
+        mut,
+        seeds=[
+            CallOptionBid::VAULT_PREFIX,
+            call_option_bid.key().as_ref()
+        ],
+        bump,
+    )]
+    /// CHECK: seeds
+    pub escrow_payment_account: UncheckedAccount<'info>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collection.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_close_expired_call_option_bid(ctx: Context<CloseExpiredCallOptionBid>, _id: u64) -> Result<()> {
+    let call_option_bid = &ctx.accounts.call_option_bid;
+    let escrow_payment_account = &ctx.accounts.escrow_payment_account;
+
+    require!(call_option_bid.is_expired(ctx.accounts.clock.unix_timestamp), ErrorCodes::NotExpired);
+
+    let call_option_bid_pubkey = call_option_bid.key();
+    let signer_bump = &[call_option_bid.escrow_bump];
+    let signer_seeds = &[&[
+        CallOptionBid::VAULT_PREFIX,
+        call_option_bid_pubkey.as_ref(),
+        signer_bump
+    ][..]];
+
+    // Return the full vault balance, including its rent, to the buyer
+    invoke_signed(
+        &transfer(
+            &escrow_payment_account.key(),
+            &call_option_bid.buyer,
+            escrow_payment_account.lamports(),
+        ),
+        &[
+            escrow_payment_account.to_account_info(),
+            ctx.accounts.buyer.to_account_info(),
+        ],
+        signer_seeds
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredCallOption<'info> {
+    /// Pays for the thaw, as the seller is not required to sign
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: constrained on call_option
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+            &call_option.sequence.to_le_bytes(),
+        ],
+        bump,
+        has_one = seller,
+        has_one = mint,
+        constraint = call_option.state == CallOptionState::Listed @ ErrorCodes::InvalidState,
+        close = seller,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    #[account(
+        mut,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref(),
+        ],
+        bump,
+        constraint = token_manager.authority.unwrap() == seller.key() @ ErrorCodes::Unauthorized,
+    )]   
+    pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = seller
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    /// CHECK: validated in cpi
+    pub deposit_token_record: Option<UncheckedAccount<'info>>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    /// CHECK: deserialized and checked
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub authorization_rules: Option<UncheckedAccount<'info>>, 
+    /// Misc
+    /// CHECK: validated in cpi
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_close_expired_call_option(ctx: Context<CloseExpiredCallOption>) -> Result<()> {
+    let token_manager = &mut ctx.accounts.token_manager;
+    let payer = &ctx.accounts.payer;
+    let seller = &ctx.accounts.seller;
+    let deposit_token_account = &ctx.accounts.deposit_token_account;
+    let mint = &ctx.accounts.mint;
+    let metadata = &ctx.accounts.metadata;
+    let edition = &ctx.accounts.edition;
+    let deposit_token_record = &ctx.accounts.deposit_token_record;
+    let token_program = &ctx.accounts.token_program;
+    let system_program = &ctx.accounts.system_program;
+    let sysvar_instructions = &ctx.accounts.sysvar_instructions;
+    let authorization_rules_program = &ctx.accounts.authorization_rules_program;
+    let authorization_rules = &ctx.accounts.authorization_rules;
+
+    require!(
+        ctx.accounts.call_option.is_listing_expired(ctx.accounts.clock.unix_timestamp),
+        ErrorCodes::NotExpired
+    );
+
+    token_manager.accounts.call_option = false;
+
+    // IMPORTANT CHECK!
+    if token_manager.accounts.rental == false {
+        handle_thaw(
+            token_manager,
+            seller.to_account_info(),
+            payer.to_account_info(),
+            deposit_token_account.to_account_info(),
+            match deposit_token_record {
+                Some(token_record) => Some(token_record.to_account_info()),
+                None => None,
+            },
+            mint.to_account_info(),
+            metadata.to_account_info(),
+            edition.to_account_info(),
+            token_program.to_account_info(),
+            system_program.to_account_info(),
+            sysvar_instructions.to_account_info(),
+            authorization_rules_program.to_account_info(),
+            match authorization_rules {
+                Some(authorization_rules) => Some(authorization_rules.to_account_info()),
+                None => None,
+            },
+        )?;
+
+        // Only the seller can revoke the token manager as delegate. When someone else
+        // closes the listing it stays delegated, but can only be frozen again by the seller
+        if seller.is_signer {
+            handle_revoke(
+                token_manager,
+                seller.to_account_info(),
+                deposit_token_account.to_account_info(),
+                match deposit_token_record {
+                    Some(token_record) => Some(token_record.to_account_info()),
+                    None => None,
+                },
+                mint.to_account_info(),
+                metadata.to_account_info(),
+                edition.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                sysvar_instructions.to_account_info(),
+                authorization_rules_program.to_account_info(),
+                match authorization_rules {
+                    Some(authorization_rules) => Some(authorization_rules.to_account_info()),
+                    None => None,
+                },
+            )?;
+        }
+
+        token_manager.release();
+    }
+
+    Ok(())
+}
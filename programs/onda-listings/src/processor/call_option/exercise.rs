@@ -5,14 +5,9 @@ use anchor_spl::{token::{Mint, Token, TokenAccount}, associated_token::{Associat
 use crate::state::{CallOption, CallOptionState, TokenManager};
 use crate::error::{ErrorCodes};
 use crate::utils::*;
-use crate::constants::*;
 
 #[derive(Accounts)]
 pub struct ExerciseCallOption<'info> {
-    #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
-    )]
-    pub signer: Signer<'info>,
     /// CHECK: contrained on listing_account
     #[account(mut)]
     pub seller: AccountInfo<'info>,
@@ -24,6 +19,7 @@ pub struct ExerciseCallOption<'info> {
             CallOption::PREFIX,
             mint.key().as_ref(),
             seller.key().as_ref(),
+            &call_option.sequence.to_le_bytes(),
         ],
         bump,
         has_one = mint,
@@ -127,8 +123,12 @@ pub fn handle_exercise_call_option<'info>(ctx: Context<'_, '_, '_, 'info, Exerci
     token_manager.accounts.call_option = false;
     token_manager.accounts.rental = false;
 
-    let remaining_amount = pay_creator_royalties(
+    let remaining_amount = pay_creator_fees(
         call_option.strike_price,
+        call_option.exercise_basis_points,
+        call_option.verified_creators_only,
+        // The payout only shares the instrument fees
+        None,
         &mint.to_account_info(),
         &metadata_info.to_account_info(),
         &mut buyer.to_account_info(),
@@ -0,0 +1,213 @@
+use {
+    anchor_lang::{
+        prelude::*,
+        Discriminator,
+        solana_program::{
+            program::{invoke_signed},
+            system_instruction::{transfer}
+        },
+    },
+    anchor_spl::token::{Mint}
+};
+use crate::state::{CallOption, LegacyCallOption, CallOptionBid, LegacyCallOptionBid, Collection, OfferCounter};
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct MigrateCallOption<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: constrained on legacy_call_option
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+    /// CHECK: deserialized from the legacy layout
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub legacy_call_option: UncheckedAccount<'info>,
+    // Legacy options were opened before sequencing, which starts from the migrated token manager's zero
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+            &0u64.to_le_bytes(),
+        ],
+        space = CallOption::space(),
+        bump,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: validated in assert_metadata_valid
+    pub metadata: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Call options hold no funds, so anyone may pay to move one to its sequenced address
+pub fn handle_migrate_call_option(ctx: Context<MigrateCallOption>) -> Result<()> {
+    let legacy_call_option_info = ctx.accounts.legacy_call_option.to_account_info();
+
+    // Only call options still in the original layout can be migrated
+    let legacy_call_option = {
+        let data = legacy_call_option_info.try_borrow_data()?;
+
+        require_eq!(data.len(), LegacyCallOption::space(), ErrorCodes::InvalidState);
+        require!(data[..8] == CallOption::DISCRIMINATOR, ErrorCodes::InvalidState);
+
+        LegacyCallOption::deserialize(&mut &data[8..])?
+    };
+
+    require_keys_eq!(legacy_call_option.seller, ctx.accounts.seller.key(), ErrorCodes::InvalidState);
+    require_keys_eq!(legacy_call_option.mint, ctx.accounts.mint.key(), ErrorCodes::InvalidMint);
+
+    assert_metadata_valid(&ctx.accounts.metadata, &ctx.accounts.mint.to_account_info())?;
+    let exercise_basis_points = get_seller_fee_basis_points(&ctx.accounts.metadata)?;
+
+    ctx.accounts.call_option.set_inner(legacy_call_option.migrate(
+        exercise_basis_points,
+        *ctx.bumps.get("call_option").unwrap(),
+    ));
+
+    // The seller paid the legacy account's rent
+    close_legacy_account(&legacy_call_option_info, &ctx.accounts.seller.to_account_info())?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(legacy_id: u8)]
+pub struct MigrateCallOptionBid<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [
+            OfferCounter::PREFIX,
+            buyer.key().as_ref(),
+        ],
+        payer = buyer,
+        space = OfferCounter::space(),
+        bump,
+    )]
+    pub offer_counter: Box<Account<'info, OfferCounter>>,
+    /// CHECK: deserialized from the legacy layout
+    #[account(
+        mut,
+        seeds = [
+            CallOptionBid::PREFIX,
+            collection.mint.as_ref(),
+            buyer.key().as_ref(),
+            &[legacy_id],
+        ],
+        bump,
+    )]
+    pub legacy_call_option_bid: UncheckedAccount<'info>,
+    /// CHECK: seeds
+    #[account(
+        mut,
+        seeds = [
+            CallOptionBid::VAULT_PREFIX,
+            legacy_call_option_bid.key().as_ref()
+        ],
+        bump,
+    )]
+    pub legacy_escrow_payment_account: UncheckedAccount<'info>,
+    #[account(
+        init,
+        seeds = [
+            CallOptionBid::PREFIX,
+            collection.mint.as_ref(),
+            buyer.key().as_ref(),
+            &offer_counter.next_id.to_le_bytes(),
+        ],
+        payer = buyer,
+        space = CallOptionBid::space(),
+        bump,
+    )]
+    pub call_option_bid: Box<Account<'info, CallOptionBid>>,
+    /// CHECK: seeds, created by the transfer from the legacy vault
+    #[account(
+        mut,
+        seeds = [
+            CallOptionBid::VAULT_PREFIX,
+            call_option_bid.key().as_ref()
+        ],
+        bump,
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collection.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    pub system_program: Program<'info, System>,
+}
+
+// Bids move to the next id from the buyer's counter, as their u8 ids could collide with counted ones
+pub fn handle_migrate_call_option_bid(
+    ctx: Context<MigrateCallOptionBid>,
+    _legacy_id: u8,
+) -> Result<()> {
+    let legacy_call_option_bid_info = ctx.accounts.legacy_call_option_bid.to_account_info();
+    let legacy_escrow_payment_account = &ctx.accounts.legacy_escrow_payment_account;
+    let offer_counter = &mut ctx.accounts.offer_counter;
+
+    // Only bids still in the original layout can be migrated
+    let legacy_call_option_bid = {
+        let data = legacy_call_option_bid_info.try_borrow_data()?;
+
+        require_eq!(data.len(), LegacyCallOptionBid::space(), ErrorCodes::InvalidState);
+        require!(data[..8] == CallOptionBid::DISCRIMINATOR, ErrorCodes::InvalidState);
+
+        LegacyCallOptionBid::deserialize(&mut &data[8..])?
+    };
+
+    require_keys_eq!(legacy_call_option_bid.buyer, ctx.accounts.buyer.key(), ErrorCodes::Unauthorized);
+
+    ctx.accounts.call_option_bid.set_inner(legacy_call_option_bid.migrate(
+        offer_counter.next_id,
+        *ctx.bumps.get("call_option_bid").unwrap(),
+        *ctx.bumps.get("escrow_payment_account").unwrap(),
+    ));
+
+    offer_counter.owner = ctx.accounts.buyer.key();
+    offer_counter.bump = *ctx.bumps.get("offer_counter").unwrap();
+    offer_counter.next_id = offer_counter.next_id.checked_add(1).ok_or(ErrorCodes::NumericalOverflow)?;
+
+    let legacy_call_option_bid_pubkey = legacy_call_option_bid_info.key();
+    let signer_bump = &[legacy_call_option_bid.escrow_bump];
+    let signer_seeds = &[&[
+        CallOptionBid::VAULT_PREFIX,
+        legacy_call_option_bid_pubkey.as_ref(),
+        signer_bump
+    ][..]];
+
+    // The whole vault moves, including its rent
+    invoke_signed(
+        &transfer(
+            &legacy_escrow_payment_account.key(),
+            &ctx.accounts.escrow_payment_account.key(),
+            legacy_escrow_payment_account.lamports(),
+        ),
+        &[
+            legacy_escrow_payment_account.to_account_info(),
+            ctx.accounts.escrow_payment_account.to_account_info(),
+        ],
+        signer_seeds
+    )?;
+
+    close_legacy_account(&legacy_call_option_bid_info, &ctx.accounts.buyer.to_account_info())?;
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,13 +1,21 @@
 pub mod ask;
+pub mod batch;
 pub mod bid;
 pub mod buy;
 pub mod close;
 pub mod exercise;
+pub mod migrate;
+pub mod reclaim;
 pub mod sell;
+pub mod update;
 
 pub use ask::*;
+pub use batch::*;
 pub use bid::*;
 pub use buy::*;
 pub use close::*;
 pub use exercise::*;
-pub use sell::*;
\ No newline at end of file
+pub use migrate::*;
+pub use reclaim::*;
+pub use sell::*;
+pub use update::*;
\ No newline at end of file
@@ -0,0 +1,167 @@
+use {
+    anchor_lang::{prelude::*},
+    anchor_spl::token::{Mint, Token, TokenAccount}
+};
+use crate::state::{CallOption, CallOptionState, TokenManager};
+use crate::error::{ErrorCodes};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct ReclaimCallOption<'info> {
+    /// Anyone may reclaim a settled option, and receives any tip held by the option account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: constrained on call_option
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+    /// Only required while an expired option still has the token frozen
+    #[account(
+        mut,
+        constraint = deposit_token_account.owner == seller.key(),
+        constraint = deposit_token_account.mint == mint.key(),
+    )]
+    pub deposit_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut)]
+    /// CHECK: validated in cpi
+    pub deposit_token_record: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+            &call_option.sequence.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint,
+        has_one = seller,
+        constraint = call_option.state != CallOptionState::Listed @ ErrorCodes::InvalidState,
+        // The seller paid for the option account when it was created
+        close = seller,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    #[account(
+        mut,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub token_manager: Box<Account<'info, TokenManager>>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    /// CHECK: deserialized and checked
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// Misc
+    /// CHECK: not supported by anchor? used in cpi
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_reclaim_call_option(ctx: Context<ReclaimCallOption>) -> Result<()> {
+    let call_option = &ctx.accounts.call_option;
+    let token_manager = &mut ctx.accounts.token_manager;
+    let payer = &ctx.accounts.payer;
+    let seller = &ctx.accounts.seller;
+    let deposit_token_account = &ctx.accounts.deposit_token_account;
+    let deposit_token_record = &ctx.accounts.deposit_token_record;
+    let mint = &ctx.accounts.mint;
+    let metadata = &ctx.accounts.metadata;
+    let edition = &ctx.accounts.edition;
+    let token_program = &ctx.accounts.token_program;
+    let system_program = &ctx.accounts.system_program;
+    let sysvar_instructions = &ctx.accounts.sysvar_instructions;
+    let authorization_rules_program = &ctx.accounts.authorization_rules_program;
+    let authorization_rules = &ctx.accounts.authorization_rules;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if call_option.state == CallOptionState::Active {
+        if call_option.expiry > unix_timestamp {
+            return Err(ErrorCodes::OptionNotExpired.into())
+        }
+    }
+
+    // IMPORTANT CHECK!
+    // The token manager is only released if it has not moved on to a later position on the mint
+    if token_manager.sequence == call_option.sequence && token_manager.authority == Some(seller.key()) {
+        // IMPORTANT CHECK!
+        if token_manager.accounts.rental == false {
+            // An exercised token has already been transferred to the buyer
+            if call_option.state == CallOptionState::Active {
+                let deposit_token_account = match deposit_token_account {
+                    Some(deposit_token_account) => deposit_token_account,
+                    None => return err!(ErrorCodes::InvalidState),
+                };
+
+                handle_thaw(
+                    token_manager,
+                    seller.to_account_info(),
+                    payer.to_account_info(),
+                    deposit_token_account.to_account_info(),
+                    match deposit_token_record {
+                        Some(token_record) => Some(token_record.to_account_info()),
+                        None => None,
+                    },
+                    mint.to_account_info(),
+                    metadata.to_account_info(),
+                    edition.to_account_info(),
+                    token_program.to_account_info(),
+                    system_program.to_account_info(),
+                    sysvar_instructions.to_account_info(),
+                    authorization_rules_program.to_account_info(),
+                    match authorization_rules {
+                        Some(authorization_rules) => Some(authorization_rules.to_account_info()),
+                        None => None,
+                    },
+                )?;
+
+                // Only the seller can revoke the token manager as delegate. When someone else
+                // reclaims the option it stays delegated, but can only be frozen again by the seller
+                if seller.is_signer {
+                    handle_revoke(
+                        token_manager,
+                        seller.to_account_info(),
+                        deposit_token_account.to_account_info(),
+                        match deposit_token_record {
+                            Some(token_record) => Some(token_record.to_account_info()),
+                            None => None,
+                        },
+                        mint.to_account_info(),
+                        metadata.to_account_info(),
+                        edition.to_account_info(),
+                        token_program.to_account_info(),
+                        system_program.to_account_info(),
+                        sysvar_instructions.to_account_info(),
+                        authorization_rules_program.to_account_info(),
+                        match authorization_rules {
+                            Some(authorization_rules) => Some(authorization_rules.to_account_info()),
+                            None => None,
+                        },
+                    )?;
+                }
+            }
+
+            token_manager.release();
+        } else {
+            token_manager.accounts.call_option = false;
+        }
+    }
+
+    pay_reclaim_tip(
+        &ctx.accounts.call_option.to_account_info(),
+        &payer.to_account_info(),
+    )?;
+
+    Ok(())
+}
\ No newline at end of file
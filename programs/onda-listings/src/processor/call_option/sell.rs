@@ -8,18 +8,26 @@ use {
     },
     anchor_spl::token::{Mint, Token, TokenAccount}
 };
-use crate::state::{CallOption, CallOptionBid, Collection, TokenManager};
+use crate::state::{CallOption, CallOptionBid, CallOptionTerms, Collection, ReferrerStats, TokenManager, Treasury, GlobalConfig};
 use crate::utils::*;
 use crate::error::*;
-use crate::constants::*;
 
 #[derive(Accounts)]
-#[instruction(id: u8)]
+#[instruction(id: u64)]
 pub struct SellCallOption<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
     )]
-    pub signer: Signer<'info>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    /// Only required with an attestation
+    #[account(mut)]
+    /// CHECK: derived and created in assert_attested
+    pub attestation_receipt: Option<UncheckedAccount<'info>>,
     #[account(mut)]
     pub seller: Signer<'info>,
     #[account(mut)]
@@ -28,12 +36,28 @@ pub struct SellCallOption<'info> {
     #[account(
         mut,
         constraint = deposit_token_account.amount == 1,
+        constraint = deposit_token_account.owner == seller.key(),
         constraint = deposit_token_account.mint == mint.key(),
     )]
     pub deposit_token_account: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
     /// CHECK: validated in cpi
     pub deposit_token_record: Option<UncheckedAccount<'info>>,
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref()
+        ],
+        space = TokenManager::space(),
+        bump,
+        constraint = (
+            token_manager.authority == Some(seller.key()) ||
+            token_manager.authority == None
+        ) @ ErrorCodes::Unauthorized,
+    )]
+    pub token_manager: Box<Account<'info, TokenManager>>,
     #[account(
         init,
         payer = seller,
@@ -41,6 +65,7 @@ pub struct SellCallOption<'info> {
             CallOption::PREFIX,
             mint.key().as_ref(),
             seller.key().as_ref(),
+            &token_manager.next_sequence().to_le_bytes(),
         ],
         space = CallOption::space(),
         bump,
@@ -52,10 +77,12 @@ pub struct SellCallOption<'info> {
             CallOptionBid::PREFIX,
             collection.mint.as_ref(),
             buyer.key().as_ref(),
-            &[id],
+            &id.to_le_bytes(),
         ],
         close = buyer,
         bump,
+        constraint = call_option_bid.mint.is_none() || call_option_bid.mint == Some(mint.key()) @ ErrorCodes::InvalidMint,
+        constraint = call_option_bid.allows_counterparty(&seller.key()) @ ErrorCodes::InvalidCounterparty,
     )]
     pub call_option_bid: Box<Account<'info, CallOptionBid>>,
     #[account(
@@ -69,26 +96,36 @@ pub struct SellCallOption<'info> {
     /// CHECK: seeds
     pub escrow_payment_account: UncheckedAccount<'info>,
     #[account(
-        init_if_needed,
-        payer = seller,
         seeds = [
-            TokenManager::PREFIX,
-            seller.key().as_ref()
+            Collection::PREFIX,
+            collection.mint.as_ref(),
         ],
-        space = TokenManager::space(),
         bump,
-        constraint = token_manager.authority.unwrap() == seller.key() @ ErrorCodes::Unauthorized,
+        constraint = collection.config.option_enabled && global_config.flags.options_enabled
     )]
-    pub token_manager: Box<Account<'info, TokenManager>>,
+    pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: derived and deserialized if initialized
+    pub denylist: UncheckedAccount<'info>,
     #[account(
+        mut,
         seeds = [
-            Collection::PREFIX,
-            collection.mint.as_ref(),
+            Treasury::PREFIX,
         ],
         bump,
-        constraint = collection.config.option_enabled == true
     )]
-    pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: deserialized if initialized
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: constrained on referrer_stats
+    pub referrer: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [
+            ReferrerStats::PREFIX,
+            referrer_stats.referrer.as_ref(),
+        ],
+        bump,
+    )]
+    pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
     #[account(constraint = mint.supply == 1)]
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: deserialized and checked
@@ -112,8 +149,21 @@ pub struct SellCallOption<'info> {
 
 pub fn handle_sell_call_option<'info>(
   ctx: Context<'_, '_, '_, 'info, SellCallOption<'info>>,
-  _id: u8,
+  _id: u64,
+  proof: Vec<[u8; 32]>,
+  expected_terms: CallOptionTerms,
+  collection_proof: Vec<[u8; 32]>,
 ) -> Result<()> {
+    assert_attested(
+        &ctx.accounts.global_config,
+        &ctx.accounts.signer,
+        &ctx.accounts.attestation_receipt,
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.sysvar_instructions,
+        ctx.accounts.collection.config.permissionless,
+    )?;
+
     let call_option = &mut ctx.accounts.call_option;
     let bid = &mut ctx.accounts.call_option_bid;
     let seller = &ctx.accounts.seller;
@@ -137,11 +187,27 @@ pub fn handle_sell_call_option<'info>(
     assert_collection_valid(
         &ctx.accounts.metadata,
         ctx.accounts.mint.key(),
-        collection.key(),
-        ctx.program_id.clone(),
+        collection,
+        &collection_proof,
     )?;
+    assert_mint_not_denied(
+        &ctx.accounts.denylist,
+        &ctx.accounts.collection.key(),
+        &ctx.accounts.mint.key(),
+        ctx.program_id,
+    )?;
+
+    if let Some(merkle_root) = bid.merkle_root {
+        assert_merkle_proof_valid(&proof, merkle_root, mint.key())?;
+    }
 
     require_eq!(token_manager.accounts.loan, false, ErrorCodes::InvalidState);
+    // Positions are no longer unique per PDA, so only one may be open on a mint at a time
+    require_eq!(token_manager.accounts.call_option, false, ErrorCodes::InvalidState);
+    require!(!bid.is_expired(unix_timestamp), ErrorCodes::OfferExpired);
+    expected_terms.assert_matches(bid.amount, bid.strike_price, bid.expiry)?;
+    // Batch updated bids are only checked against the collection's limits when sold into
+    collection.config.assert_option_expiry_valid(bid.expiry, unix_timestamp)?;
 
     // Init
     call_option.seller = seller.key();
@@ -149,10 +215,25 @@ pub fn handle_sell_call_option<'info>(
     call_option.mint = mint.key();
     call_option.bump = *ctx.bumps.get("call_option").unwrap();
     //
-    CallOption::init_ask_state(call_option, bid.amount, collection.config.option_basis_points, bid.strike_price, bid.expiry)?;
+    let creator_basis_points = get_creator_basis_points(
+        &collection.config,
+        collection.config.option_basis_points,
+        &metadata.to_account_info(),
+    )?;
+    CallOption::init_ask_state(call_option, bid.amount, creator_basis_points, bid.strike_price, bid.expiry)?;
+    call_option.exercise_basis_points = get_exercise_basis_points(
+        &collection.config,
+        &metadata.to_account_info(),
+    )?;
+    call_option.verified_creators_only = collection.config.verified_creators_only;
+    call_option.payout = collection.config.royalty_payout();
+    call_option.permissionless = collection.config.permissionless;
     CallOption::set_active(call_option, unix_timestamp)?;
     //
     token_manager.accounts.call_option = true;
+    token_manager.authority = Some(seller.key());
+    token_manager.sequence = token_manager.next_sequence();
+    call_option.sequence = token_manager.sequence;
     token_manager.bump = *ctx.bumps.get("token_manager").unwrap();
 
     let call_option_bid_pubkey = bid.key();
@@ -163,22 +244,56 @@ pub fn handle_sell_call_option<'info>(
         signer_bump
     ][..]];
 
-    let fee_basis_points = collection.config.option_basis_points;
-    pay_creator_fees_with_signer(
+    // The premium is paid out of the bid escrow
+    let remaining_amount = pay_creator_fees_with_signer(
         call_option.amount,
-        fee_basis_points, 
+        call_option.creator_basis_points,
+        call_option.verified_creators_only,
+        call_option.payout,
         &ctx.accounts.mint.to_account_info(),
         &ctx.accounts.metadata.to_account_info(),
-        &mut ctx.accounts.buyer.to_account_info(),
+        &mut escrow_payment_account.to_account_info(),
         remaining_accounts,
         signer_seeds
     )?;
 
+    // The protocol takes its share of the premium
+    let protocol_fee = get_protocol_fee(
+        &ctx.accounts.treasury,
+        &ctx.accounts.global_config,
+        &collection.config,
+        call_option.amount
+    )?;
+
+    let referral_fee = get_referral_fee(
+        &ctx.accounts.treasury,
+        &ctx.accounts.global_config,
+        protocol_fee,
+        &ctx.accounts.referrer,
+        &mut ctx.accounts.referrer_stats,
+    )?;
+
+    if let Some(referrer_stats) = &ctx.accounts.referrer_stats {
+        pay_protocol_fee_with_signer(
+            referral_fee,
+            escrow_payment_account.to_account_info(),
+            referrer_stats.to_account_info(),
+            signer_seeds
+        )?;
+    }
+
+    pay_protocol_fee_with_signer(
+        protocol_fee - referral_fee,
+        escrow_payment_account.to_account_info(),
+        ctx.accounts.treasury.to_account_info(),
+        signer_seeds
+    )?;
+
     invoke_signed(
         &transfer(
             &escrow_payment_account.key(),
             &call_option.seller,
-            bid.amount,
+            remaining_amount.checked_sub(protocol_fee).ok_or(ErrorCodes::NumericalOverflow)?,
         ),
         &[
             escrow_payment_account.to_account_info(),
@@ -192,6 +307,31 @@ pub fn handle_sell_call_option<'info>(
         if deposit_token_account.delegate.unwrap() != token_manager.key() {
             return err!(ErrorCodes::InvalidState);
         }
+
+        // An expired listing may have been thawed without revoking the delegate
+        if !deposit_token_account.is_frozen() {
+            handle_freeze(
+                token_manager,
+                seller.to_account_info(),
+                seller.to_account_info(),
+                deposit_token_account.to_account_info(),
+                match deposit_token_record {
+                    Some(token_record) => Some(token_record.to_account_info()),
+                    None => None,
+                },
+                mint.to_account_info(),
+                metadata.to_account_info(),
+                edition.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                sysvar_instructions.to_account_info(),
+                authorization_rules_program.to_account_info(),
+                match authorization_rules {
+                    Some(authorization_rules) => Some(authorization_rules.to_account_info()),
+                    None => None,
+                }
+            )?;
+        }
     } else {
         handle_delegate_and_freeze(
             token_manager,
@@ -0,0 +1,152 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{CallOption, CallOptionBid, CallOptionState, Collection, GlobalConfig};
+use crate::error::{ErrorCodes};
+use crate::utils::*;
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct UpdateCallOptionBid<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOptionBid::PREFIX,
+            collection.mint.as_ref(),
+            buyer.key().as_ref(),
+            &id.to_le_bytes(),
+        ],
+        bump,
+        has_one = buyer,
+    )]
+    pub call_option_bid: Box<Account<'info, CallOptionBid>>,
+    /// CHECK: seeds
+    #[account(
+        mut,
+        seeds=[
+            CallOptionBid::VAULT_PREFIX,
+            call_option_bid.key().as_ref()
+        ],
+        bump,
+    )]
+    pub escrow_payment_account: AccountInfo<'info>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collection.mint.as_ref(),
+        ],
+        bump,
+        constraint = signer.is_some() || collection.config.permissionless @ ErrorCodes::Unauthorized,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_update_call_option_bid(
+    ctx: Context<UpdateCallOptionBid>,
+    _id: u64,
+    amount: u64,
+    strike_price: u64,
+    expiry: i64,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    let call_option_bid = &mut ctx.accounts.call_option_bid;
+
+    CallOptionBid::assert_terms_valid(
+        &ctx.accounts.collection.config,
+        expiry,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+
+    if let Some(expires_at) = expires_at {
+        require_gt!(expires_at, ctx.accounts.clock.unix_timestamp, ErrorCodes::InvalidExpiry);
+    }
+
+    let call_option_bid_pubkey = call_option_bid.key();
+    let signer_bump = &[call_option_bid.escrow_bump];
+    let signer_seeds = &[&[
+        CallOptionBid::VAULT_PREFIX,
+        call_option_bid_pubkey.as_ref(),
+        signer_bump
+    ][..]];
+
+    adjust_escrow_balance(
+        ctx.accounts.buyer.to_account_info(),
+        ctx.accounts.escrow_payment_account.to_account_info(),
+        signer_seeds,
+        call_option_bid.amount,
+        amount,
+    )?;
+
+    call_option_bid.amount = amount;
+    call_option_bid.strike_price = strike_price;
+    call_option_bid.expiry = expiry;
+    call_option_bid.expires_at = expires_at;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateCallOption<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    pub seller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+            &call_option.sequence.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint,
+        has_one = seller,
+        constraint = call_option.state == CallOptionState::Listed @ ErrorCodes::InvalidState,
+        constraint = signer.is_some() || call_option.permissionless @ ErrorCodes::Unauthorized,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_update_call_option(
+    ctx: Context<UpdateCallOption>,
+    amount: u64,
+    strike_price: u64,
+    expiry: i64,
+    listing_expires_at: Option<i64>,
+) -> Result<()> {
+    let call_option = &mut ctx.accounts.call_option;
+    let creator_basis_points = call_option.creator_basis_points;
+
+    require_gt!(expiry, ctx.accounts.clock.unix_timestamp, ErrorCodes::InvalidExpiry);
+
+    if let Some(listing_expires_at) = listing_expires_at {
+        require_gt!(listing_expires_at, ctx.accounts.clock.unix_timestamp, ErrorCodes::InvalidExpiry);
+    }
+
+    CallOption::init_ask_state(call_option, amount, creator_basis_points, strike_price, expiry)?;
+    call_option.listing_expires_at = listing_expires_at;
+
+    Ok(())
+}
\ No newline at end of file
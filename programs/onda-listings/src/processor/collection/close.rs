@@ -1,14 +1,18 @@
 use anchor_lang::{
   prelude::*,
 };
-use anchor_spl::token::{Mint};
-use crate::state::{Collection};
-use crate::constants::*;
+use crate::state::{Collection, GlobalConfig};
+use crate::error::*;
 
 #[derive(Accounts)]
 pub struct CloseCollection<'info> {
   #[account(
-      constraint = signer.key() == SIGNER_PUBKEY
+    seeds = [GlobalConfig::PREFIX],
+    bump = global_config.bump,
+  )]
+  pub global_config: Box<Account<'info, GlobalConfig>>,
+  #[account(
+    constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
   )]
   pub signer: Signer<'info>,
   #[account(mut)]
@@ -17,13 +21,12 @@ pub struct CloseCollection<'info> {
       mut,
       seeds = [
           Collection::PREFIX,
-          mint.key().as_ref(),
+          collection.mint.as_ref(),
       ],
       bump,
       close = authority,
   )]
   pub collection: Box<Account<'info, Collection>>,
-  pub mint: Box<Account<'info, Mint>>,
   pub system_program: Program<'info, System>,
   pub rent: Sysvar<'info, Rent>,
 }
@@ -5,15 +5,20 @@ use {
     anchor_spl::token::{Mint},
 };
 
-use crate::constants::*;
-use crate::state::{Collection, Config};
+use crate::error::*;
+use crate::state::{Collection, CollectionType, Config, GlobalConfig};
 
 
 #[derive(Accounts)]
 #[instruction(config: Config)]
 pub struct InitCollection<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
     )]
     pub signer: Signer<'info>,
     #[account(mut)]
@@ -40,10 +45,65 @@ pub fn handle_init_collection(
 ) -> Result<()> {
     let collection = &mut ctx.accounts.collection;
     
-    require_keys_eq!(ctx.accounts.authority.key(), ADMIN_PUBKEY);
+    require_keys_eq!(ctx.accounts.authority.key(), ctx.accounts.global_config.admin, ErrorCodes::Unauthorized);
     
+    config.assert_valid()?;
+
     collection.authority = ctx.accounts.authority.key();
     collection.mint = ctx.accounts.mint.key();
+    collection.collection_type = CollectionType::Certified;
+    collection.bump = *ctx.bumps.get("collection").unwrap();
+    collection.config = config;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(collection_type: CollectionType, key: Pubkey)]
+pub struct InitUncertifiedCollection<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        seeds = [
+            Collection::PREFIX,
+            key.as_ref(),
+        ],
+        bump,
+        payer = authority,
+        space = Collection::space(),
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+// Collections that predate certified collections are keyed by their first verified
+// creator, or by the merkle root of their mints
+pub fn handle_init_uncertified_collection(
+    ctx: Context<InitUncertifiedCollection>,
+    collection_type: CollectionType,
+    key: Pubkey,
+    config: Config,
+) -> Result<()> {
+    let collection = &mut ctx.accounts.collection;
+    
+    require_keys_eq!(ctx.accounts.authority.key(), ctx.accounts.global_config.admin, ErrorCodes::Unauthorized);
+    require!(collection_type != CollectionType::Certified, ErrorCodes::InvalidCollection);
+    config.assert_valid()?;
+
+    collection.authority = ctx.accounts.authority.key();
+    collection.mint = key;
+    collection.collection_type = collection_type;
     collection.bump = *ctx.bumps.get("collection").unwrap();
     collection.config = config;
 
@@ -0,0 +1,84 @@
+use anchor_lang::{
+  prelude::*,
+  Discriminator,
+  solana_program::{
+    program::{invoke},
+    system_instruction::{transfer}
+  },
+};
+use anchor_spl::token::{Mint};
+use crate::state::{Collection, LegacyCollection, GlobalConfig};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct MigrateCollection<'info> {
+  #[account(
+    seeds = [GlobalConfig::PREFIX],
+    bump = global_config.bump,
+  )]
+  pub global_config: Box<Account<'info, GlobalConfig>>,
+  #[account(
+    constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+  )]
+  pub signer: Signer<'info>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  /// CHECK: deserialized from the legacy layout
+  #[account(
+    mut,
+      seeds = [
+          Collection::PREFIX,
+          mint.key().as_ref(),
+      ],
+      bump,
+  )]
+  pub collection: UncheckedAccount<'info>,
+  pub mint: Box<Account<'info, Mint>>,
+  pub system_program: Program<'info, System>,
+}
+
+pub fn handle_migrate_collection(
+  ctx: Context<MigrateCollection>
+) -> Result<()> {
+  let authority = &ctx.accounts.authority;
+  let collection_info = ctx.accounts.collection.to_account_info();
+
+  require_keys_eq!(authority.key(), ctx.accounts.global_config.admin, ErrorCodes::Unauthorized);
+
+  // Only collections still in the original layout can be migrated
+  let legacy_collection = {
+    let data = collection_info.try_borrow_data()?;
+
+    require_eq!(data.len(), LegacyCollection::space(), ErrorCodes::InvalidState);
+    require!(data[..8] == Collection::DISCRIMINATOR, ErrorCodes::InvalidState);
+
+    LegacyCollection::deserialize(&mut &data[8..])?
+  };
+
+  let space = Collection::space();
+  let rent = Rent::get()?.minimum_balance(space);
+  let top_up = rent.saturating_sub(collection_info.lamports());
+
+  if top_up > 0 {
+    invoke(
+      &transfer(
+        &authority.key(),
+        &collection_info.key(),
+        top_up,
+      ),
+      &[
+        authority.to_account_info(),
+        collection_info.to_account_info(),
+      ]
+    )?;
+  }
+
+  collection_info.realloc(space, false)?;
+
+  let collection = legacy_collection.migrate();
+  let mut data = collection_info.try_borrow_mut_data()?;
+  let mut writer: &mut [u8] = &mut data;
+  collection.try_serialize(&mut writer)?;
+
+  Ok(())
+}
\ No newline at end of file
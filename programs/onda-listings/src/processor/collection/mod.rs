@@ -1,7 +1,9 @@
 pub mod close;
 pub mod initialize;
+pub mod migrate;
 pub mod update;
 
 pub use close::*;
 pub use initialize::*;
+pub use migrate::*;
 pub use update::*;
\ No newline at end of file
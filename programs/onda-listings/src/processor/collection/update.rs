@@ -1,15 +1,19 @@
 use anchor_lang::{
   prelude::*,
 };
-use anchor_spl::token::{Mint};
-use crate::state::{Collection, Config};
-use crate::constants::*;
+use crate::state::{Collection, Config, GlobalConfig};
+use crate::error::*;
 
 #[derive(Accounts)]
 #[instruction(config: Config)]
 pub struct UpdateCollection<'info> {
   #[account(
-      constraint = signer.key() == SIGNER_PUBKEY
+    seeds = [GlobalConfig::PREFIX],
+    bump = global_config.bump,
+  )]
+  pub global_config: Box<Account<'info, GlobalConfig>>,
+  #[account(
+    constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
   )]
   pub signer: Signer<'info>,
   #[account(mut)]
@@ -18,12 +22,11 @@ pub struct UpdateCollection<'info> {
     mut,
       seeds = [
           Collection::PREFIX,
-          mint.key().as_ref(),
+          collection.mint.as_ref(),
       ],
       bump,
   )]
   pub collection: Box<Account<'info, Collection>>,
-  pub mint: Box<Account<'info, Mint>>,
   pub system_program: Program<'info, System>,
   pub rent: Sysvar<'info, Rent>,
 }
@@ -36,6 +39,15 @@ pub fn handle_update_collection(
   
   require_keys_eq!(ctx.accounts.authority.key(), collection.authority);
   
+  config.assert_valid()?;
+
+  // The protocol fee is owed to the treasury and the payout is taken from the creators,
+  // so only the admin may change either
+  if config.protocol_fee_basis_points != collection.config.protocol_fee_basis_points ||
+    config.payout != collection.config.payout {
+    require_keys_eq!(ctx.accounts.authority.key(), ctx.accounts.global_config.admin, ErrorCodes::Unauthorized);
+  }
+
   collection.config = config;
 
   Ok(())
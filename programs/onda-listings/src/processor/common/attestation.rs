@@ -0,0 +1,23 @@
+use anchor_lang::{prelude::*};
+use crate::state::{AttestationReceipt};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct CloseAttestationReceipt<'info> {
+  /// CHECK: constrained on attestation_receipt
+  #[account(mut)]
+  pub payer: AccountInfo<'info>,
+  #[account(
+    mut,
+    has_one = payer,
+    constraint = attestation_receipt.expiry <= clock.unix_timestamp @ ErrorCodes::NotExpired,
+    close = payer,
+  )]
+  pub attestation_receipt: Box<Account<'info, AttestationReceipt>>,
+  pub clock: Sysvar<'info, Clock>,
+}
+
+// An expired attestation can't be used again, so anyone may return its receipt's rent to the payer
+pub fn handle_close_attestation_receipt(_ctx: Context<CloseAttestationReceipt>) -> Result<()> {
+  Ok(())
+}
\ No newline at end of file
@@ -5,15 +5,19 @@ use {
       token::{Token, TokenAccount, Mint}
   }
 };
-use crate::state::{TokenManager};
+use crate::state::{TokenManager, GlobalConfig};
 use crate::error::{ErrorCodes};
 use crate::utils::*;
-use crate::constants::*;
 
 #[derive(Accounts)]
 pub struct Claim<'info> {
   #[account(
-      constraint = signer.key() == SIGNER_PUBKEY
+    seeds = [GlobalConfig::PREFIX],
+    bump = global_config.bump,
+  )]
+  pub global_config: Box<Account<'info, GlobalConfig>>,
+  #[account(
+    constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
   )]
   pub signer: Signer<'info>,
   #[account(mut)]
@@ -0,0 +1,74 @@
+use anchor_lang::{
+  prelude::*,
+  Discriminator,
+  solana_program::{
+    program::{invoke},
+    system_instruction::{transfer}
+  },
+};
+use anchor_spl::token::{Mint};
+use crate::state::{TokenManager, LegacyTokenManager};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct MigrateTokenManager<'info> {
+  #[account(mut)]
+  pub payer: Signer<'info>,
+  /// CHECK: deserialized from the legacy layout
+  #[account(
+    mut,
+      seeds = [
+          TokenManager::PREFIX,
+          mint.key().as_ref(),
+      ],
+      bump,
+  )]
+  pub token_manager: UncheckedAccount<'info>,
+  pub mint: Box<Account<'info, Mint>>,
+  pub system_program: Program<'info, System>,
+}
+
+// Token managers hold no funds and only gain a sequence, so anyone may pay to migrate one
+pub fn handle_migrate_token_manager(
+  ctx: Context<MigrateTokenManager>
+) -> Result<()> {
+  let payer = &ctx.accounts.payer;
+  let token_manager_info = ctx.accounts.token_manager.to_account_info();
+
+  // Only token managers still in the original layout can be migrated
+  let legacy_token_manager = {
+    let data = token_manager_info.try_borrow_data()?;
+
+    require_eq!(data.len(), LegacyTokenManager::space(), ErrorCodes::InvalidState);
+    require!(data[..8] == TokenManager::DISCRIMINATOR, ErrorCodes::InvalidState);
+
+    LegacyTokenManager::deserialize(&mut &data[8..])?
+  };
+
+  let space = TokenManager::space();
+  let rent = Rent::get()?.minimum_balance(space);
+  let top_up = rent.saturating_sub(token_manager_info.lamports());
+
+  if top_up > 0 {
+    invoke(
+      &transfer(
+        &payer.key(),
+        &token_manager_info.key(),
+        top_up,
+      ),
+      &[
+        payer.to_account_info(),
+        token_manager_info.to_account_info(),
+      ]
+    )?;
+  }
+
+  token_manager_info.realloc(space, false)?;
+
+  let token_manager = legacy_token_manager.migrate();
+  let mut data = token_manager_info.try_borrow_mut_data()?;
+  let mut writer: &mut [u8] = &mut data;
+  token_manager.try_serialize(&mut writer)?;
+
+  Ok(())
+}
\ No newline at end of file
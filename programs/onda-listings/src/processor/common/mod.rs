@@ -1,3 +1,7 @@
+pub mod attestation;
 pub mod claim;
+pub mod migrate;
 
-pub use claim::*;
\ No newline at end of file
+pub use attestation::*;
+pub use claim::*;
+pub use migrate::*;
\ No newline at end of file
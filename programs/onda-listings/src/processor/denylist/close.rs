@@ -0,0 +1,43 @@
+use anchor_lang::{prelude::*};
+use crate::state::{Collection, Denylist, GlobalConfig};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct CloseDenylist<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collection.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    #[account(
+        mut,
+        seeds = [
+            Denylist::PREFIX,
+            collection.key().as_ref(),
+        ],
+        bump,
+        has_one = collection,
+        close = authority,
+    )]
+    pub denylist: Box<Account<'info, Denylist>>,
+}
+
+pub fn handle_close_denylist(ctx: Context<CloseDenylist>) -> Result<()> {
+    require_keys_eq!(ctx.accounts.authority.key(), ctx.accounts.collection.authority);
+
+    Ok(())
+}
\ No newline at end of file
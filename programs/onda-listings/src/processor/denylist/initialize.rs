@@ -0,0 +1,50 @@
+use anchor_lang::{prelude::*};
+use crate::state::{Collection, Denylist, GlobalConfig};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct InitDenylist<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collection.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    #[account(
+        init,
+        seeds = [
+            Denylist::PREFIX,
+            collection.key().as_ref(),
+        ],
+        bump,
+        payer = authority,
+        space = Denylist::space(),
+    )]
+    pub denylist: Box<Account<'info, Denylist>>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_init_denylist(ctx: Context<InitDenylist>) -> Result<()> {
+    let denylist = &mut ctx.accounts.denylist;
+
+    require_keys_eq!(ctx.accounts.authority.key(), ctx.accounts.collection.authority);
+
+    denylist.collection = ctx.accounts.collection.key();
+    denylist.bump = *ctx.bumps.get("denylist").unwrap();
+
+    Ok(())
+}
\ No newline at end of file
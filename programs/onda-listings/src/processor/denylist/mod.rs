@@ -0,0 +1,7 @@
+pub mod close;
+pub mod initialize;
+pub mod update;
+
+pub use close::*;
+pub use initialize::*;
+pub use update::*;
\ No newline at end of file
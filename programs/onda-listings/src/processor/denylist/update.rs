@@ -0,0 +1,56 @@
+use anchor_lang::{prelude::*};
+use crate::state::{Collection, Denylist, GlobalConfig};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct UpdateDenylist<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collection.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    #[account(
+        mut,
+        seeds = [
+            Denylist::PREFIX,
+            collection.key().as_ref(),
+        ],
+        bump,
+        has_one = collection,
+    )]
+    pub denylist: Box<Account<'info, Denylist>>,
+}
+
+pub fn handle_update_denylist(
+    ctx: Context<UpdateDenylist>,
+    deny: Vec<Pubkey>,
+    allow: Vec<Pubkey>,
+) -> Result<()> {
+    let denylist = &mut ctx.accounts.denylist;
+
+    require_keys_eq!(ctx.accounts.authority.key(), ctx.accounts.collection.authority);
+
+    // Positions already open on a denied mint are not affected
+    for mint in deny {
+        denylist.deny(mint)?;
+    }
+
+    for mint in allow.iter() {
+        denylist.allow(mint);
+    }
+
+    Ok(())
+}
\ No newline at end of file
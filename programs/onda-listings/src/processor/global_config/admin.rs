@@ -0,0 +1,24 @@
+use anchor_lang::{prelude::*};
+use crate::state::{GlobalConfig};
+
+#[derive(Accounts)]
+pub struct SetGlobalConfigAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [
+            GlobalConfig::PREFIX,
+        ],
+        bump = global_config.bump,
+        has_one = admin,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    pub admin: Signer<'info>,
+    /// Signs so the admin can't be handed to a key nobody holds
+    pub new_admin: Signer<'info>,
+}
+
+pub fn handle_set_global_config_admin(ctx: Context<SetGlobalConfigAdmin>) -> Result<()> {
+    ctx.accounts.global_config.admin = ctx.accounts.new_admin.key();
+
+    Ok(())
+}
\ No newline at end of file
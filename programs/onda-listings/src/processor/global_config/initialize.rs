@@ -0,0 +1,53 @@
+use anchor_lang::{prelude::*};
+use crate::program::OndaListings;
+use crate::state::{GlobalConfig, GlobalFlags, ProtocolFees};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct InitGlobalConfig<'info> {
+    /// The program's upgrade authority
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        seeds = [
+            GlobalConfig::PREFIX,
+        ],
+        bump,
+        payer = payer,
+        space = GlobalConfig::space(),
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub program: Program<'info, OndaListings>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+// There is no config to check the caller against yet, so only the upgrade authority may create it
+pub fn handle_init_global_config(
+    ctx: Context<InitGlobalConfig>,
+    admin: Pubkey,
+    signers: Vec<Pubkey>,
+    fees: ProtocolFees,
+    flags: GlobalFlags,
+) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+
+    GlobalConfig::assert_signers_valid(&signers)?;
+    fees.assert_valid()?;
+
+    global_config.admin = admin;
+    global_config.signers = signers;
+    global_config.fees = fees;
+    global_config.flags = flags;
+    global_config.bump = *ctx.bumps.get("global_config").unwrap();
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+pub mod admin;
+pub mod initialize;
+pub mod update;
+
+pub use admin::*;
+pub use initialize::*;
+pub use update::*;
\ No newline at end of file
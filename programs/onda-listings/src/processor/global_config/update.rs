@@ -0,0 +1,35 @@
+use anchor_lang::{prelude::*};
+use crate::state::{GlobalConfig, GlobalFlags, ProtocolFees};
+
+#[derive(Accounts)]
+pub struct UpdateGlobalConfig<'info> {
+    #[account(
+        mut,
+        seeds = [
+            GlobalConfig::PREFIX,
+        ],
+        bump = global_config.bump,
+        has_one = admin,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    pub admin: Signer<'info>,
+}
+
+// Replacing the signers rotates the co-signing keys without a redeploy
+pub fn handle_update_global_config(
+    ctx: Context<UpdateGlobalConfig>,
+    signers: Vec<Pubkey>,
+    fees: ProtocolFees,
+    flags: GlobalFlags,
+) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+
+    GlobalConfig::assert_signers_valid(&signers)?;
+    fees.assert_valid()?;
+
+    global_config.signers = signers;
+    global_config.fees = fees;
+    global_config.flags = flags;
+
+    Ok(())
+}
\ No newline at end of file
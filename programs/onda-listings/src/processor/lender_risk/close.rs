@@ -0,0 +1,36 @@
+use anchor_lang::{prelude::*};
+use crate::state::{LenderRisk, GlobalConfig};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct CloseLenderRisk<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            LenderRisk::PREFIX,
+            lender.key().as_ref(),
+        ],
+        bump,
+        has_one = lender,
+        close = lender,
+    )]
+    pub lender_risk: Box<Account<'info, LenderRisk>>,
+}
+
+pub fn handle_close_lender_risk(ctx: Context<CloseLenderRisk>) -> Result<()> {
+    // Active loans release their exposure on settlement
+    require_eq!(ctx.accounts.lender_risk.active_loans, 0, ErrorCodes::InvalidState);
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,45 @@
+use anchor_lang::{prelude::*};
+use crate::state::{LenderRisk, RiskLimits, GlobalConfig};
+use crate::error::*;
+
+#[derive(Accounts)]
+#[instruction(limits: RiskLimits)]
+pub struct InitLenderRisk<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    #[account(
+        init,
+        seeds = [
+            LenderRisk::PREFIX,
+            lender.key().as_ref(),
+        ],
+        bump,
+        payer = lender,
+        space = LenderRisk::space(),
+    )]
+    pub lender_risk: Box<Account<'info, LenderRisk>>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_init_lender_risk(
+    ctx: Context<InitLenderRisk>,
+    limits: RiskLimits,
+) -> Result<()> {
+    let lender_risk = &mut ctx.accounts.lender_risk;
+
+    lender_risk.lender = ctx.accounts.lender.key();
+    lender_risk.limits = limits;
+    lender_risk.bump = *ctx.bumps.get("lender_risk").unwrap();
+
+    Ok(())
+}
\ No newline at end of file
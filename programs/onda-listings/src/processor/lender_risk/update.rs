@@ -0,0 +1,40 @@
+use anchor_lang::{prelude::*};
+use crate::state::{LenderRisk, RiskLimits, GlobalConfig};
+use crate::error::*;
+
+#[derive(Accounts)]
+#[instruction(limits: RiskLimits)]
+pub struct UpdateLenderRisk<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    pub lender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            LenderRisk::PREFIX,
+            lender.key().as_ref(),
+        ],
+        bump,
+        has_one = lender,
+    )]
+    pub lender_risk: Box<Account<'info, LenderRisk>>,
+}
+
+pub fn handle_update_lender_risk(
+    ctx: Context<UpdateLenderRisk>,
+    limits: RiskLimits,
+) -> Result<()> {
+    let lender_risk = &mut ctx.accounts.lender_risk;
+
+    // New limits only apply to loans activated from now on
+    lender_risk.limits = limits;
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,6 +1,6 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{Loan, Collection, TokenManager};
+use crate::state::{Loan, Collection, TokenManager, GlobalConfig};
 use crate::utils::*;
 use crate::error::*;
 use crate::constants::*;
@@ -9,29 +9,27 @@ use crate::constants::*;
 #[instruction(amount: u64, basis_points: u16, duration: u64)]
 pub struct AskLoan<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
     )]
-    pub signer: Signer<'info>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    /// Only required with an attestation
+    #[account(mut)]
+    /// CHECK: derived and created in assert_attested
+    pub attestation_receipt: Option<UncheckedAccount<'info>>,
     #[account(mut)]
     pub borrower: Signer<'info>,
     #[account(
         mut,
         constraint = deposit_token_account.amount == 1,
+        constraint = deposit_token_account.owner == borrower.key(),
         constraint = deposit_token_account.mint == mint.key(),
     )]
     pub deposit_token_account: Box<Account<'info, TokenAccount>>,
-    #[account(
-        init,
-        payer = borrower,
-        seeds = [
-            Loan::PREFIX,
-            mint.key().as_ref(),
-            borrower.key().as_ref(),
-        ],
-        space = Loan::space(),
-        bump,
-    )]
-    pub loan: Box<Account<'info, Loan>>,
     #[account(
         init_if_needed,
         payer = borrower,
@@ -47,15 +45,30 @@ pub struct AskLoan<'info> {
         ) @ ErrorCodes::Unauthorized,
     )]   
     pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(
+        init,
+        payer = borrower,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+            &token_manager.next_sequence().to_le_bytes(),
+        ],
+        space = Loan::space(),
+        bump,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
     #[account(
         seeds = [
             Collection::PREFIX,
             collection.mint.as_ref(),
         ],
         bump,
-        constraint = collection.config.loan_enabled == true
+        constraint = collection.config.loan_enabled && global_config.flags.loans_enabled
     )]
     pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: derived and deserialized if initialized
+    pub denylist: UncheckedAccount<'info>,
     #[account(constraint = mint.supply == 1)]
     pub mint: Box<Account<'info, Mint>>,
     #[account(mut)]
@@ -77,6 +90,7 @@ pub struct AskLoan<'info> {
     pub sysvar_instructions: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
     pub rent: Sysvar<'info, Rent>,
 }
 
@@ -84,8 +98,22 @@ pub fn handle_ask_loan(
   ctx: Context<AskLoan>,
   amount: u64,
   basis_points: u16,
-  duration: i64
+  duration: i64,
+  listing_expires_at: Option<i64>,
+  counterparties: Vec<Pubkey>,
+  auction_ends_at: Option<i64>,
+  collection_proof: Vec<[u8; 32]>,
 ) -> Result<()> {
+    assert_attested(
+        &ctx.accounts.global_config,
+        &ctx.accounts.signer,
+        &ctx.accounts.attestation_receipt,
+        &ctx.accounts.borrower.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.sysvar_instructions,
+        ctx.accounts.collection.config.permissionless,
+    )?;
+
     let loan = &mut ctx.accounts.loan;
     let borrower = &ctx.accounts.borrower;
     let token_manager = &mut ctx.accounts.token_manager;
@@ -100,25 +128,62 @@ pub fn handle_ask_loan(
     let sysvar_instructions = &ctx.accounts.sysvar_instructions;
     let authorization_rules_program = &ctx.accounts.authorization_rules_program;
     let authorization_rules = &ctx.accounts.authorization_rules;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
 
     assert_collection_valid(
         &ctx.accounts.metadata,
         ctx.accounts.mint.key(),
-        ctx.accounts.collection.key(),
-        ctx.program_id.clone(),
+        &ctx.accounts.collection,
+        &collection_proof,
+    )?;
+    assert_mint_not_denied(
+        &ctx.accounts.denylist,
+        &ctx.accounts.collection.key(),
+        &ctx.accounts.mint.key(),
+        ctx.program_id,
     )?;
 
+    collection.config.assert_loan_terms_valid(amount, basis_points, duration)?;
+
+    if let Some(listing_expires_at) = listing_expires_at {
+        require_gt!(listing_expires_at, unix_timestamp, ErrorCodes::InvalidExpiry);
+    }
+
+    // In an auction basis_points is the highest rate the borrower will accept
+    if let Some(auction_ends_at) = auction_ends_at {
+        require_gt!(auction_ends_at, unix_timestamp, ErrorCodes::InvalidExpiry);
+        require!(listing_expires_at.is_none(), ErrorCodes::InvalidExpiry);
+    }
+
+    require_gte!(MAX_COUNTERPARTIES, counterparties.len(), ErrorCodes::TooManyCounterparties);
+
     require_eq!(token_manager.accounts.call_option, false, ErrorCodes::InvalidState);
+    // Positions are no longer unique per PDA, so only one may be open on a mint at a time
+    require_eq!(token_manager.accounts.loan, false, ErrorCodes::InvalidState);
 
     // Init
     loan.mint = ctx.accounts.mint.key();
     loan.borrower = ctx.accounts.borrower.key();
     loan.bump = *ctx.bumps.get("loan").unwrap();
     //
-    Loan::init_ask_state(loan, amount, collection.config.loan_basis_points, basis_points, duration)?;
+    let creator_basis_points = get_creator_basis_points(
+        &collection.config,
+        collection.config.loan_basis_points,
+        &metadata.to_account_info(),
+    )?;
+    Loan::init_ask_state(loan, amount, creator_basis_points, basis_points, duration)?;
+    loan.verified_creators_only = collection.config.verified_creators_only;
+    loan.payout = collection.config.royalty_payout();
+    loan.permissionless = collection.config.permissionless;
+    loan.listing_expires_at = listing_expires_at;
+    loan.collection = collection.key();
+    loan.counterparties = counterparties;
+    loan.auction_ends_at = auction_ends_at;
     //
     token_manager.accounts.loan = true;
     token_manager.authority = Some(borrower.key());
+    token_manager.sequence = token_manager.next_sequence();
+    loan.sequence = token_manager.sequence;
     token_manager.bump = *ctx.bumps.get("token_manager").unwrap();
 
     // Freeze deposit token account
@@ -126,6 +191,31 @@ pub fn handle_ask_loan(
         if deposit_token_account.delegate.unwrap() != token_manager.key() {
             return err!(ErrorCodes::InvalidState);
         }
+
+        // An expired listing may have been thawed without revoking the delegate
+        if !deposit_token_account.is_frozen() {
+            handle_freeze(
+                token_manager,
+                borrower.to_account_info(),
+                borrower.to_account_info(),
+                deposit_token_account.to_account_info(),
+                match token_record {
+                    Some(token_record) => Some(token_record.to_account_info()),
+                    None => None,
+                },
+                mint.to_account_info(),
+                metadata.to_account_info(),
+                edition.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                sysvar_instructions.to_account_info(),
+                authorization_rules_program.to_account_info(),
+                match authorization_rules {
+                    Some(authorization_rules) => Some(authorization_rules.to_account_info()),
+                    None => None,
+                }
+            )?;
+        }
     } else {
         handle_delegate_and_freeze(
             token_manager,
@@ -0,0 +1,163 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        program::{invoke_signed},
+        system_instruction::{transfer}
+    },
+    AccountsClose
+};
+use crate::state::{LoanOffer, Collection, LoanTerms, GlobalConfig};
+use crate::error::{ErrorCodes};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct CloseLoanOffers<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    // remaining accounts: [loan_offer, escrow_payment_account, collection] triples
+}
+
+#[derive(Accounts)]
+pub struct UpdateLoanOffers<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    // remaining accounts: [loan_offer, escrow_payment_account, collection] triples
+}
+
+fn load_loan_offer<'info>(
+    accounts: &[AccountInfo<'info>],
+    lender: &Pubkey,
+    signer: &Option<Signer<'info>>,
+    program_id: &Pubkey,
+) -> Result<(Account<'info, LoanOffer>, Account<'info, Collection>)> {
+    let loan_offer = Account::<LoanOffer>::try_from(&accounts[0])?;
+    let collection = Account::<Collection>::try_from(&accounts[2])?;
+
+    require_keys_eq!(loan_offer.lender, *lender, ErrorCodes::Unauthorized);
+    require_keys_eq!(loan_offer.collection, collection.key(), ErrorCodes::InvalidCollection);
+
+    assert_batch_accounts_valid(
+        accounts,
+        &collection,
+        signer,
+        &[
+            LoanOffer::PREFIX,
+            collection.mint.as_ref(),
+            lender.as_ref(),
+            &loan_offer.id.to_le_bytes(),
+            &[loan_offer.bump],
+        ],
+        LoanOffer::VAULT_PREFIX,
+        loan_offer.escrow_bump,
+        program_id,
+    )?;
+
+    Ok((loan_offer, collection))
+}
+
+pub fn handle_close_loan_offers<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseLoanOffers<'info>>,
+) -> Result<()> {
+    let lender = &ctx.accounts.lender;
+
+    require!(ctx.remaining_accounts.len() % 3 == 0, ErrorCodes::InvalidRemainingAccounts);
+
+    for accounts in ctx.remaining_accounts.chunks(3) {
+        let escrow_payment_account = &accounts[1];
+        let (loan_offer, _) = load_loan_offer(accounts, &lender.key(), &ctx.accounts.signer, ctx.program_id)?;
+
+        let loan_offer_pubkey = loan_offer.key();
+        let signer_bump = &[loan_offer.escrow_bump];
+        let signer_seeds = &[&[
+            LoanOffer::VAULT_PREFIX,
+            loan_offer_pubkey.as_ref(),
+            signer_bump
+        ][..]];
+
+        // Return the full vault balance, including its rent, to the lender
+        invoke_signed(
+            &transfer(
+                &escrow_payment_account.key(),
+                &lender.key(),
+                escrow_payment_account.lamports(),
+            ),
+            &[
+                escrow_payment_account.clone(),
+                lender.to_account_info(),
+            ],
+            signer_seeds
+        )?;
+
+        loan_offer.close(lender.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+pub fn handle_update_loan_offers<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateLoanOffers<'info>>,
+    terms: Vec<LoanTerms>,
+) -> Result<()> {
+    let lender = &ctx.accounts.lender;
+
+    require!(ctx.remaining_accounts.len() % 3 == 0, ErrorCodes::InvalidRemainingAccounts);
+    require_eq!(ctx.remaining_accounts.len() / 3, terms.len(), ErrorCodes::InvalidRemainingAccounts);
+
+    for (accounts, terms) in ctx.remaining_accounts.chunks(3).zip(terms.iter()) {
+        let escrow_payment_account = &accounts[1];
+        let (mut loan_offer, collection) = load_loan_offer(accounts, &lender.key(), &ctx.accounts.signer, ctx.program_id)?;
+
+        LoanOffer::assert_terms_valid(
+            &collection.config,
+            terms.amount,
+            terms.basis_points,
+            terms.duration,
+            &loan_offer.rate_curve,
+        )?;
+
+        let loan_offer_pubkey = loan_offer.key();
+        let signer_bump = &[loan_offer.escrow_bump];
+        let signer_seeds = &[&[
+            LoanOffer::VAULT_PREFIX,
+            loan_offer_pubkey.as_ref(),
+            signer_bump
+        ][..]];
+
+        adjust_escrow_balance(
+            lender.to_account_info(),
+            escrow_payment_account.clone(),
+            signer_seeds,
+            loan_offer.amount.unwrap(),
+            terms.amount,
+        )?;
+
+        loan_offer.amount = Some(terms.amount);
+        loan_offer.basis_points = terms.basis_points;
+        loan_offer.duration = terms.duration;
+        loan_offer.exit(ctx.program_id)?;
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,162 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        program::{invoke, invoke_signed},
+        system_instruction::{transfer}
+    }
+};
+use anchor_spl::token::{Mint};
+use crate::state::{Loan, LoanState, LenderRisk, GlobalConfig};
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+#[instruction(basis_points: u16)]
+pub struct BidLoan<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    /// CHECK: contrained on loan_account
+    pub borrower: AccountInfo<'info>,
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    /// CHECK: constrained on loan_account
+    #[account(mut)]
+    pub previous_lender: Option<UncheckedAccount<'info>>,
+    /// CHECK: derived from previous_lender
+    #[account(mut)]
+    pub previous_lender_risk: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+            &loan.sequence.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint,
+        has_one = borrower,
+        constraint = loan.borrower != lender.key(),
+        constraint = loan.state == LoanState::Listed,
+        constraint = loan.auction_ends_at.is_some() @ ErrorCodes::InvalidListingType,
+        constraint = loan.allows_counterparty(&lender.key()) @ ErrorCodes::InvalidCounterparty,
+        constraint = signer.is_some() || loan.permissionless @ ErrorCodes::Unauthorized,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::AUCTION_VAULT_PREFIX,
+            loan.key().as_ref(),
+        ],
+        bump,
+    )]
+    /// CHECK: seeds
+    pub escrow_payment_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            LenderRisk::PREFIX,
+            lender.key().as_ref(),
+        ],
+        bump,
+    )]
+    /// CHECK: deserialized if initialized
+    pub lender_risk: UncheckedAccount<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_bid_loan(ctx: Context<BidLoan>, basis_points: u16) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let lender = &ctx.accounts.lender;
+    let escrow_payment_account = &ctx.accounts.escrow_payment_account;
+
+    require!(!loan.is_auction_ended(ctx.accounts.clock.unix_timestamp), ErrorCodes::AuctionEnded);
+
+    // The first bid may match the borrower's maximum rate, every later bid must beat the current one
+    if loan.lender.is_some() {
+        require_gt!(loan.basis_points, basis_points, ErrorCodes::BidTooHigh);
+    } else {
+        require_gte!(loan.basis_points, basis_points, ErrorCodes::BidTooHigh);
+    }
+
+    // Refund the bid being replaced
+    if let Some(previous_lender_key) = loan.lender {
+        let previous_lender = match &ctx.accounts.previous_lender {
+            Some(previous_lender) => previous_lender,
+            None => return err!(ErrorCodes::InvalidState),
+        };
+
+        require_keys_eq!(previous_lender.key(), previous_lender_key, ErrorCodes::InvalidState);
+
+        // The replaced bid's exposure is released, as it was reserved when the bid was placed
+        if loan.exposure_recorded {
+            let previous_lender_risk = match &ctx.accounts.previous_lender_risk {
+                Some(previous_lender_risk) => previous_lender_risk,
+                None => return err!(ErrorCodes::InvalidState),
+            };
+
+            let (previous_lender_risk_address, _) = Pubkey::find_program_address(
+                &[
+                    LenderRisk::PREFIX,
+                    previous_lender_key.as_ref(),
+                ],
+                ctx.program_id,
+            );
+            require_keys_eq!(previous_lender_risk.key(), previous_lender_risk_address, ErrorCodes::DerivedKeyInvalid);
+
+            release_lender_exposure(previous_lender_risk, loan, loan.outstanding, true, ctx.program_id)?;
+            loan.exposure_recorded = false;
+        }
+
+        let loan_pubkey = loan.key();
+        let signer_bump = &[*ctx.bumps.get("escrow_payment_account").unwrap()];
+        let signer_seeds = &[&[
+            Loan::AUCTION_VAULT_PREFIX,
+            loan_pubkey.as_ref(),
+            signer_bump
+        ][..]];
+
+        invoke_signed(
+            &transfer(
+                &escrow_payment_account.key(),
+                &previous_lender_key,
+                loan.amount.unwrap(),
+            ),
+            &[
+                escrow_payment_account.to_account_info(),
+                previous_lender.to_account_info(),
+            ],
+            signer_seeds
+        )?;
+    }
+
+    invoke(
+        &transfer(
+            &lender.key(),
+            &escrow_payment_account.key(),
+            loan.amount.unwrap(),
+        ),
+        &[
+            lender.to_account_info(),
+            escrow_payment_account.to_account_info(),
+        ]
+    )?;
+
+    loan.lender = Some(lender.key());
+    loan.basis_points = basis_points;
+
+    // Exposure is reserved while the bid stands, so the lender's limits hold through settlement
+    record_lender_exposure(&ctx.accounts.lender_risk, loan, ctx.program_id)?;
+
+    Ok(())
+}
\ No newline at end of file
@@ -5,22 +5,16 @@ use {
             program::{invoke_signed},
             system_instruction::{transfer}
         },
-        AccountsClose
     },
     anchor_spl::token::{Mint, Token, TokenAccount}
 };
 
-use crate::state::{Collection, Loan, LoanState, LoanOffer, TokenManager};
+use crate::state::{Collection, Loan, LoanState, LoanOffer, TokenManager, GlobalConfig};
 use crate::error::{ErrorCodes};
 use crate::utils::*;
-use crate::constants::*;
 
 #[derive(Accounts)]
 pub struct CloseLoan<'info> {
-    #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
-    )]
-    pub signer: Signer<'info>,
     pub borrower: Signer<'info>,
     #[account(
         mut,
@@ -36,11 +30,14 @@ pub struct CloseLoan<'info> {
             Loan::PREFIX,
             mint.key().as_ref(),
             borrower.key().as_ref(),
+            &loan.sequence.to_le_bytes(),
         ],
         bump,
         has_one = mint,
         has_one = borrower,
         constraint = loan.state != LoanState::Active @ ErrorCodes::InvalidState,
+        // An auction with a standing bid can only be settled
+        constraint = loan.state != LoanState::Listed || loan.lender.is_none() @ ErrorCodes::InvalidState,
         close = borrower,
     )]
     pub loan: Box<Account<'info, Loan>>,
@@ -115,7 +112,7 @@ pub fn handle_close_loan(ctx: Context<CloseLoan>) -> Result<()> {
                 },
             )?;
         
-            token_manager.close(borrower.to_account_info())?;    
+            token_manager.release();
         } else {
             token_manager.accounts.loan = false;
         }   
@@ -125,12 +122,17 @@ pub fn handle_close_loan(ctx: Context<CloseLoan>) -> Result<()> {
 }
 
 #[derive(Accounts)]
-#[instruction(id: u8)]
+#[instruction(id: u64)]
 pub struct CloseLoanOffer<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
     )]
-    pub signer: Signer<'info>,
+    pub signer: Option<Signer<'info>>,
     #[account(mut)]
     pub lender: Signer<'info>,
     #[account(
@@ -139,7 +141,7 @@ pub struct CloseLoanOffer<'info> {
             LoanOffer::PREFIX,
             collection.mint.as_ref(),
             lender.key().as_ref(),
-            &[id],
+            &id.to_le_bytes(),
         ],
         close = lender,
         bump,
@@ -161,6 +163,7 @@ pub struct CloseLoanOffer<'info> {
             collection.mint.as_ref(),
         ],
         bump,
+        constraint = signer.is_some() || collection.config.permissionless @ ErrorCodes::Unauthorized,
     )]
     pub collection: Box<Account<'info, Collection>>,
     /// Misc
@@ -168,7 +171,7 @@ pub struct CloseLoanOffer<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn handle_close_loan_offer(ctx: Context<CloseLoanOffer>, _id: u8) -> Result<()> {
+pub fn handle_close_loan_offer(ctx: Context<CloseLoanOffer>, _id: u64) -> Result<()> {
     let loan_offer = &ctx.accounts.loan_offer;
     let escrow_payment_account = &ctx.accounts.escrow_payment_account;
 
@@ -180,11 +183,12 @@ pub fn handle_close_loan_offer(ctx: Context<CloseLoanOffer>, _id: u8) -> Result<
         signer_bump
     ][..]];
 
+    // Return the full vault balance, including its rent, to the lender
     invoke_signed(
         &transfer(
             &escrow_payment_account.key(),
             &loan_offer.lender,
-            loan_offer.amount.unwrap(),
+            escrow_payment_account.lamports(),
         ),
         &[
             escrow_payment_account.to_account_info(),
@@ -194,4 +198,218 @@ pub fn handle_close_loan_offer(ctx: Context<CloseLoanOffer>, _id: u8) -> Result<
     )?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CloseExpiredLoanOffer<'info> {
+    /// CHECK: constrained on loan_offer
+    #[account(mut)]
+    pub lender: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            LoanOffer::PREFIX,
+            collection.mint.as_ref(),
+            lender.key().as_ref(),
+            &id.to_le_bytes(),
+        ],
+        bump,
+        has_one = lender,
+        close = lender,
+    )]
+    pub loan_offer: Box<Account<'info, LoanOffer>>,
+    /// CHECK: seeds
+    #[account(
+        mut,
+        seeds=[
+            LoanOffer::VAULT_PREFIX,
+            loan_offer.key().as_ref()
+        ],
+        bump,
+    )]
+    pub escrow_payment_account: AccountInfo<'info>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collection.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_close_expired_loan_offer(ctx: Context<CloseExpiredLoanOffer>, _id: u64) -> Result<()> {
+    let loan_offer = &ctx.accounts.loan_offer;
+    let escrow_payment_account = &ctx.accounts.escrow_payment_account;
+
+    require!(loan_offer.is_expired(ctx.accounts.clock.unix_timestamp), ErrorCodes::NotExpired);
+
+    let loan_offer_pubkey = loan_offer.key();
+    let signer_bump = &[loan_offer.escrow_bump];
+    let signer_seeds = &[&[
+        LoanOffer::VAULT_PREFIX,
+        loan_offer_pubkey.as_ref(),
+        signer_bump
+    ][..]];
+
+    // Return the full vault balance, including its rent, to the lender
+    invoke_signed(
+        &transfer(
+            &escrow_payment_account.key(),
+            &loan_offer.lender,
+            escrow_payment_account.lamports(),
+        ),
+        &[
+            escrow_payment_account.to_account_info(),
+            ctx.accounts.lender.to_account_info(),
+        ],
+        signer_seeds
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredLoan<'info> {
+    /// Pays for the thaw, as the borrower is not required to sign
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: constrained on loan
+    #[account(mut)]
+    pub borrower: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = deposit_token_account.owner == borrower.key(),
+        constraint = deposit_token_account.mint == mint.key(),
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    /// CHECK: validated in cpi
+    pub deposit_token_record: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+            &loan.sequence.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint,
+        has_one = borrower,
+        constraint = loan.state == LoanState::Listed @ ErrorCodes::InvalidState,
+        close = borrower,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]   
+    pub token_manager: Box<Account<'info, TokenManager>>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    /// CHECK: deserialized and checked
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub authorization_rules: Option<UncheckedAccount<'info>>, 
+    /// Misc
+    /// CHECK: not supported by anchor? used in cpi
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_close_expired_loan(ctx: Context<CloseExpiredLoan>) -> Result<()> {
+    let token_manager = &mut ctx.accounts.token_manager;
+    let payer = &ctx.accounts.payer;
+    let borrower = &ctx.accounts.borrower;
+    let deposit_token_account = &ctx.accounts.deposit_token_account;
+    let mint = &ctx.accounts.mint;
+    let metadata = &ctx.accounts.metadata;
+    let edition = &ctx.accounts.edition;
+    let deposit_token_record = &ctx.accounts.deposit_token_record;
+    let token_program = &ctx.accounts.token_program;
+    let system_program = &ctx.accounts.system_program;
+    let sysvar_instructions = &ctx.accounts.sysvar_instructions;
+    let authorization_rules_program = &ctx.accounts.authorization_rules_program;
+    let authorization_rules = &ctx.accounts.authorization_rules;
+
+    require!(
+        ctx.accounts.loan.is_listing_expired(ctx.accounts.clock.unix_timestamp),
+        ErrorCodes::NotExpired
+    );
+
+    // IMPORTANT CHECK!
+    if token_manager.authority.unwrap().eq(&borrower.key()) {
+        // IMPORTANT CHECK!
+        if token_manager.accounts.rental == false {
+            handle_thaw(
+                token_manager,
+                borrower.to_account_info(),
+                payer.to_account_info(),
+                deposit_token_account.to_account_info(),
+                match deposit_token_record {
+                    Some(token_record) => Some(token_record.to_account_info()),
+                    None => None,
+                },
+                mint.to_account_info(),
+                metadata.to_account_info(),
+                edition.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                sysvar_instructions.to_account_info(),
+                authorization_rules_program.to_account_info(),
+                match authorization_rules {
+                    Some(authorization_rules) => Some(authorization_rules.to_account_info()),
+                    None => None,
+                },
+            )?;
+
+            // Only the borrower can revoke the token manager as delegate. When someone else
+            // closes the listing it stays delegated, but can only be frozen again by the borrower
+            if borrower.is_signer {
+                handle_revoke(
+                    token_manager,
+                    borrower.to_account_info(),
+                    deposit_token_account.to_account_info(),
+                    match deposit_token_record {
+                        Some(token_record) => Some(token_record.to_account_info()),
+                        None => None,
+                    },
+                    mint.to_account_info(),
+                    metadata.to_account_info(),
+                    edition.to_account_info(),
+                    token_program.to_account_info(),
+                    system_program.to_account_info(),
+                    sysvar_instructions.to_account_info(),
+                    authorization_rules_program.to_account_info(),
+                    match authorization_rules {
+                        Some(authorization_rules) => Some(authorization_rules.to_account_info()),
+                        None => None,
+                    },
+                )?;
+            }
+
+            token_manager.release();
+        } else {
+            token_manager.accounts.loan = false;
+        }
+    }
+
+    Ok(())
+}
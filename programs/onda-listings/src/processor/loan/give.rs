@@ -1,15 +1,24 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token};
-use crate::state::{Loan, LoanState, TokenManager};
+use crate::state::{Collection, Loan, LoanState, LoanTerms, LenderRisk, ReferrerStats, TokenManager, Treasury, GlobalConfig};
 use crate::error::{ErrorCodes};
-use crate::constants::*;
+use crate::utils::*;
 
 #[derive(Accounts)]
 pub struct GiveLoan<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
     )]
-    pub signer: Signer<'info>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    /// Only required with an attestation
+    #[account(mut)]
+    /// CHECK: derived and created in assert_attested
+    pub attestation_receipt: Option<UncheckedAccount<'info>>,
     /// CHECK: contrained on loan_account
     #[account(mut)]
     pub borrower: AccountInfo<'info>,
@@ -21,14 +30,31 @@ pub struct GiveLoan<'info> {
             Loan::PREFIX,
             mint.key().as_ref(),
             borrower.key().as_ref(),
+            &loan.sequence.to_le_bytes(),
         ],
         bump,
         has_one = mint,
         has_one = borrower,
         constraint = loan.borrower != lender.key(),
         constraint = loan.state == LoanState::Listed,
+        constraint = loan.allows_counterparty(&lender.key()) @ ErrorCodes::InvalidCounterparty,
+        constraint = loan.auction_ends_at.is_none() @ ErrorCodes::InvalidListingType,
     )]
     pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            LenderRisk::PREFIX,
+            lender.key().as_ref(),
+        ],
+        bump,
+    )]
+    /// CHECK: deserialized if initialized
+    pub lender_risk: UncheckedAccount<'info>,
+    #[account(
+        constraint = collection.key() == loan.collection @ ErrorCodes::InvalidCollection,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
     #[account(
         mut,
         seeds = [
@@ -39,25 +65,87 @@ pub struct GiveLoan<'info> {
         bump,
     )]   
     pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(
+        mut,
+        seeds = [
+            Treasury::PREFIX,
+        ],
+        bump,
+    )]
+    /// CHECK: deserialized if initialized
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: constrained on referrer_stats
+    pub referrer: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [
+            ReferrerStats::PREFIX,
+            referrer_stats.referrer.as_ref(),
+        ],
+        bump,
+    )]
+    pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
     pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: address checked when the attestation is loaded
+    pub sysvar_instructions: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub clock: Sysvar<'info, Clock>,
 }
 
 
-pub fn handle_give_loan(ctx: Context<GiveLoan>) -> Result<()> {
+pub fn handle_give_loan(ctx: Context<GiveLoan>, expected_terms: LoanTerms) -> Result<()> {
+    assert_attested(
+        &ctx.accounts.global_config,
+        &ctx.accounts.signer,
+        &ctx.accounts.attestation_receipt,
+        &ctx.accounts.lender.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.sysvar_instructions,
+        ctx.accounts.collection.config.permissionless,
+    )?;
+
     let loan = &mut ctx.accounts.loan;
 
+    require!(!loan.is_listing_expired(ctx.accounts.clock.unix_timestamp), ErrorCodes::ListingExpired);
+    expected_terms.assert_matches(loan.amount.unwrap(), loan.basis_points, loan.duration)?;
+    // Updated asks are only checked against the collection's limits when given
+    ctx.accounts.collection.config.assert_loan_terms_valid(loan.amount.unwrap(), loan.basis_points, loan.duration)?;
+
     loan.lender = Some(ctx.accounts.lender.key());
     Loan::set_active(loan, ctx.accounts.clock.unix_timestamp)?;
+    record_lender_exposure(&ctx.accounts.lender_risk, loan, ctx.program_id)?;
+
+    // The origination fee is deducted from the amount the borrower receives
+    let origination_fee = get_origination_fee(&ctx.accounts.treasury, &ctx.accounts.global_config, loan.amount.unwrap())?;
+    let referral_fee = get_referral_fee(
+        &ctx.accounts.treasury,
+        &ctx.accounts.global_config,
+        origination_fee,
+        &ctx.accounts.referrer,
+        &mut ctx.accounts.referrer_stats,
+    )?;
+
+    if let Some(referrer_stats) = &ctx.accounts.referrer_stats {
+        pay_protocol_fee(
+            referral_fee,
+            ctx.accounts.lender.to_account_info(),
+            referrer_stats.to_account_info(),
+        )?;
+    }
+
+    pay_protocol_fee(
+        origination_fee - referral_fee,
+        ctx.accounts.lender.to_account_info(),
+        ctx.accounts.treasury.to_account_info(),
+    )?;
 
     // Transfer amount
     anchor_lang::solana_program::program::invoke(
         &anchor_lang::solana_program::system_instruction::transfer(
             &loan.lender.unwrap(),
             &loan.borrower,
-            loan.amount.unwrap(),
+            loan.amount.unwrap() - origination_fee,
         ),
         &[
             ctx.accounts.lender.to_account_info(),
@@ -0,0 +1,229 @@
+use {
+    anchor_lang::{
+        prelude::*,
+        Discriminator,
+        solana_program::{
+            program::{invoke_signed},
+            system_instruction::{transfer}
+        },
+    },
+    anchor_spl::token::{Mint}
+};
+use crate::state::{Collection, Loan, LegacyLoan, LoanOffer, LegacyLoanOffer, OfferCounter};
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct MigrateLoan<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: constrained on legacy_loan
+    #[account(mut)]
+    pub borrower: UncheckedAccount<'info>,
+    /// CHECK: deserialized from the legacy layout
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub legacy_loan: UncheckedAccount<'info>,
+    // Legacy loans were opened before sequencing, which starts from the migrated token manager's zero
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+            &0u64.to_le_bytes(),
+        ],
+        space = Loan::space(),
+        bump,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collection.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: validated in assert_metadata_valid
+    pub metadata: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Loans hold no funds, so anyone may pay to move one to its sequenced address
+pub fn handle_migrate_loan(
+    ctx: Context<MigrateLoan>,
+    collection_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let legacy_loan_info = ctx.accounts.legacy_loan.to_account_info();
+
+    // Only loans still in the original layout can be migrated
+    let legacy_loan = {
+        let data = legacy_loan_info.try_borrow_data()?;
+
+        require_eq!(data.len(), LegacyLoan::space(), ErrorCodes::InvalidState);
+        require!(data[..8] == Loan::DISCRIMINATOR, ErrorCodes::InvalidState);
+
+        LegacyLoan::deserialize(&mut &data[8..])?
+    };
+
+    require_keys_eq!(legacy_loan.borrower, ctx.accounts.borrower.key(), ErrorCodes::InvalidState);
+    require_keys_eq!(legacy_loan.mint, ctx.accounts.mint.key(), ErrorCodes::InvalidMint);
+
+    assert_metadata_valid(&ctx.accounts.metadata, &ctx.accounts.mint.to_account_info())?;
+    assert_collection_valid(
+        &ctx.accounts.metadata,
+        ctx.accounts.mint.key(),
+        &ctx.accounts.collection,
+        &collection_proof,
+    )?;
+
+    ctx.accounts.loan.set_inner(legacy_loan.migrate(
+        ctx.accounts.collection.key(),
+        *ctx.bumps.get("loan").unwrap(),
+    ));
+
+    // The borrower paid the legacy account's rent
+    close_legacy_account(&legacy_loan_info, &ctx.accounts.borrower.to_account_info())?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(legacy_id: u8)]
+pub struct MigrateLoanOffer<'info> {
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [
+            OfferCounter::PREFIX,
+            lender.key().as_ref(),
+        ],
+        payer = lender,
+        space = OfferCounter::space(),
+        bump,
+    )]
+    pub offer_counter: Box<Account<'info, OfferCounter>>,
+    /// CHECK: deserialized from the legacy layout
+    #[account(
+        mut,
+        seeds = [
+            LoanOffer::PREFIX,
+            collection.mint.as_ref(),
+            lender.key().as_ref(),
+            &[legacy_id],
+        ],
+        bump,
+    )]
+    pub legacy_loan_offer: UncheckedAccount<'info>,
+    /// CHECK: seeds
+    #[account(
+        mut,
+        seeds = [
+            LoanOffer::VAULT_PREFIX,
+            legacy_loan_offer.key().as_ref()
+        ],
+        bump,
+    )]
+    pub legacy_escrow_payment_account: UncheckedAccount<'info>,
+    #[account(
+        init,
+        seeds = [
+            LoanOffer::PREFIX,
+            collection.mint.as_ref(),
+            lender.key().as_ref(),
+            &offer_counter.next_id.to_le_bytes(),
+        ],
+        payer = lender,
+        space = LoanOffer::space(),
+        bump,
+    )]
+    pub loan_offer: Box<Account<'info, LoanOffer>>,
+    /// CHECK: seeds, created by the transfer from the legacy vault
+    #[account(
+        mut,
+        seeds = [
+            LoanOffer::VAULT_PREFIX,
+            loan_offer.key().as_ref()
+        ],
+        bump,
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collection.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    pub system_program: Program<'info, System>,
+}
+
+// Offers move to the next id from the lender's counter, as their u8 ids could collide with counted ones
+pub fn handle_migrate_loan_offer(
+    ctx: Context<MigrateLoanOffer>,
+    _legacy_id: u8,
+) -> Result<()> {
+    let legacy_loan_offer_info = ctx.accounts.legacy_loan_offer.to_account_info();
+    let legacy_escrow_payment_account = &ctx.accounts.legacy_escrow_payment_account;
+    let offer_counter = &mut ctx.accounts.offer_counter;
+
+    // Only offers still in the original layout can be migrated
+    let legacy_loan_offer = {
+        let data = legacy_loan_offer_info.try_borrow_data()?;
+
+        require_eq!(data.len(), LegacyLoanOffer::space(), ErrorCodes::InvalidState);
+        require!(data[..8] == LoanOffer::DISCRIMINATOR, ErrorCodes::InvalidState);
+
+        LegacyLoanOffer::deserialize(&mut &data[8..])?
+    };
+
+    require_keys_eq!(legacy_loan_offer.lender, ctx.accounts.lender.key(), ErrorCodes::Unauthorized);
+
+    ctx.accounts.loan_offer.set_inner(legacy_loan_offer.migrate(
+        offer_counter.next_id,
+        *ctx.bumps.get("loan_offer").unwrap(),
+        *ctx.bumps.get("escrow_payment_account").unwrap(),
+    ));
+
+    offer_counter.owner = ctx.accounts.lender.key();
+    offer_counter.bump = *ctx.bumps.get("offer_counter").unwrap();
+    offer_counter.next_id = offer_counter.next_id.checked_add(1).ok_or(ErrorCodes::NumericalOverflow)?;
+
+    let legacy_loan_offer_pubkey = legacy_loan_offer_info.key();
+    let signer_bump = &[legacy_loan_offer.escrow_bump];
+    let signer_seeds = &[&[
+        LoanOffer::VAULT_PREFIX,
+        legacy_loan_offer_pubkey.as_ref(),
+        signer_bump
+    ][..]];
+
+    // The whole vault moves, including its rent
+    invoke_signed(
+        &transfer(
+            &legacy_escrow_payment_account.key(),
+            &ctx.accounts.escrow_payment_account.key(),
+            legacy_escrow_payment_account.lamports(),
+        ),
+        &[
+            legacy_escrow_payment_account.to_account_info(),
+            ctx.accounts.escrow_payment_account.to_account_info(),
+        ],
+        signer_seeds
+    )?;
+
+    close_legacy_account(&legacy_loan_offer_info, &ctx.accounts.lender.to_account_info())?;
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,15 +1,27 @@
 pub mod ask;
+pub mod batch;
+pub mod bid;
 pub mod close;
 pub mod give;
+pub mod migrate;
 pub mod offer;
+pub mod reclaim;
 pub mod repay;
 pub mod repossess;
+pub mod settle;
 pub mod take;
+pub mod update;
 
 pub use ask::*;
+pub use batch::*;
+pub use bid::*;
 pub use close::*;
 pub use give::*;
+pub use migrate::*;
 pub use offer::*;
+pub use reclaim::*;
 pub use repay::*;
 pub use repossess::*;
-pub use take::*;
\ No newline at end of file
+pub use settle::*;
+pub use take::*;
+pub use update::*;
\ No newline at end of file
@@ -1,23 +1,45 @@
 use anchor_lang::{system_program, prelude::*};
-use crate::state::{LoanOffer, Collection};
+use crate::state::{LoanOffer, OfferCounter, RatePoint, Collection, GlobalConfig};
+use crate::error::*;
+use crate::utils::*;
 use crate::constants::*;
 
 #[derive(Accounts)]
-#[instruction(amount: u64, basis_points: u16, duration: u64, id: u8)]
+#[instruction(amount: u64, basis_points: u16, duration: u64)]
 pub struct OfferLoan<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
     )]
-    pub signer: Signer<'info>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    /// Only required with an attestation
+    #[account(mut)]
+    /// CHECK: derived and created in assert_attested
+    pub attestation_receipt: Option<UncheckedAccount<'info>>,
     #[account(mut)]
     pub lender: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [
+            OfferCounter::PREFIX,
+            lender.key().as_ref(),
+        ],
+        payer = lender,
+        space = OfferCounter::space(),
+        bump,
+    )]
+    pub offer_counter: Box<Account<'info, OfferCounter>>,
     #[account(
         init,
         seeds = [
             LoanOffer::PREFIX,
             collection.mint.as_ref(),
             lender.key().as_ref(),
-            &[id],
+            &offer_counter.next_id.to_le_bytes(),
         ],
         payer = lender,
         space = LoanOffer::space(),
@@ -43,12 +65,15 @@ pub struct OfferLoan<'info> {
             collection.mint.as_ref(),
         ],
         bump,
-        constraint = collection.config.loan_enabled == true
+        constraint = collection.config.loan_enabled && global_config.flags.loans_enabled
     )]
     pub collection: Box<Account<'info, Collection>>,
     /// Misc
+    /// CHECK: address checked when the attestation is loaded
+    pub sysvar_instructions: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+    pub clock: Sysvar<'info, Clock>,
 }
 
 pub fn handle_offer_loan(
@@ -56,22 +81,58 @@ pub fn handle_offer_loan(
   amount: u64,
   basis_points: u16,
   duration: i64,
-  offer_id: u8,
+  expires_at: Option<i64>,
+  mint: Option<Pubkey>,
+  merkle_root: Option<[u8; 32]>,
+  collections: Vec<Pubkey>,
+  counterparties: Vec<Pubkey>,
+  rate_curve: Vec<RatePoint>,
 ) -> Result<()> {
+    assert_attested(
+        &ctx.accounts.global_config,
+        &ctx.accounts.signer,
+        &ctx.accounts.attestation_receipt,
+        &ctx.accounts.lender.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.sysvar_instructions,
+        ctx.accounts.collection.config.permissionless,
+    )?;
+
     let offer = &mut ctx.accounts.loan_offer;
+    let offer_counter = &mut ctx.accounts.offer_counter;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if let Some(expires_at) = expires_at {
+        require_gt!(expires_at, unix_timestamp, ErrorCodes::InvalidExpiry);
+    }
+
+    require_gte!(MAX_OFFER_COLLECTIONS, collections.len(), ErrorCodes::TooManyCollections);
+    require_gte!(MAX_COUNTERPARTIES, counterparties.len(), ErrorCodes::TooManyCounterparties);
+    LoanOffer::assert_rate_curve_valid(&rate_curve)?;
+    LoanOffer::assert_terms_valid(&ctx.accounts.collection.config, amount, basis_points, duration, &rate_curve)?;
 
     // Init
     offer.collection = ctx.accounts.collection.key();
     offer.bump = *ctx.bumps.get("loan_offer").unwrap();
     offer.escrow_bump = *ctx.bumps.get("escrow_payment_account").unwrap();
     //
-    offer.id = offer_id;
+    offer.id = offer_counter.next_id;
     offer.lender = ctx.accounts.lender.key();
     offer.amount = Some(amount);
     offer.basis_points = basis_points;
     offer.duration = duration;
     offer.ltv = None;
     offer.threshold = None;
+    offer.expires_at = expires_at;
+    offer.mint = mint;
+    offer.merkle_root = merkle_root;
+    offer.collections = collections;
+    offer.counterparties = counterparties;
+    offer.rate_curve = rate_curve;
+    //
+    offer_counter.owner = ctx.accounts.lender.key();
+    offer_counter.bump = *ctx.bumps.get("offer_counter").unwrap();
+    offer_counter.next_id = offer_counter.next_id.checked_add(1).ok_or(ErrorCodes::NumericalOverflow)?;
 
     // Transfer amount
     anchor_lang::solana_program::program::invoke(
@@ -7,17 +7,17 @@ use anchor_lang::{
 };
 use anchor_spl::token::{Mint};
 use crate::utils::*;
-use crate::constants::*;
 use crate::error::*;
-use crate::state::{Loan, LoanState};
+use crate::state::{Loan, LoanState, LenderRisk, Collection, Treasury, GlobalConfig};
 
 #[derive(Accounts)]
 #[instruction(amount: u64)]
 pub struct RepayLoan<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
     )]
-    pub signer: Signer<'info>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
     #[account(mut)]
     pub borrower: Signer<'info>,
     /// CHECK: contrained on loan_account
@@ -29,14 +29,38 @@ pub struct RepayLoan<'info> {
             Loan::PREFIX,
             mint.key().as_ref(),
             borrower.key().as_ref(),
+            &loan.sequence.to_le_bytes(),
         ],
         bump,
         has_one = borrower,
         has_one = mint,
         constraint = loan.lender.unwrap() == lender.key(), 
-        constraint = loan.state == LoanState::Active
+        constraint = loan.state == LoanState::Active,
     )]
     pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            LenderRisk::PREFIX,
+            lender.key().as_ref(),
+        ],
+        bump,
+    )]
+    /// CHECK: deserialized if initialized
+    pub lender_risk: UncheckedAccount<'info>,
+    #[account(
+        constraint = collection.key() == loan.collection @ ErrorCodes::InvalidCollection,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    #[account(
+        mut,
+        seeds = [
+            Treasury::PREFIX,
+        ],
+        bump,
+    )]
+    /// CHECK: deserialized if initialized
+    pub treasury: UncheckedAccount<'info>,
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: deserialized and checked
     pub metadata: UncheckedAccount<'info>,
@@ -65,11 +89,25 @@ pub fn handle_repay_loan<'info>(ctx: Context<'_, '_, '_, 'info, RepayLoan<'info>
     )?;
     let amount_due = payment.checked_add(interest_due).ok_or(ErrorCodes::NumericalOverflow)?;
 
+    // The protocol takes its share of the interest
+    let protocol_fee = get_protocol_fee(
+        &ctx.accounts.treasury,
+        &ctx.accounts.global_config,
+        &ctx.accounts.collection.config,
+        interest_due
+    )?;
+
+    pay_protocol_fee(
+        protocol_fee,
+        borrower.to_account_info(),
+        ctx.accounts.treasury.to_account_info(),
+    )?;
+
     invoke(
         &transfer(
             &loan.borrower,
             &loan.lender.unwrap(),
-            amount_due,
+            amount_due - protocol_fee,
         ),
         &[
             borrower.to_account_info(),
@@ -87,6 +125,8 @@ pub fn handle_repay_loan<'info>(ctx: Context<'_, '_, '_, 'info, RepayLoan<'info>
     pay_creator_fees(
         creator_fee,
         10_000, // 100%
+        loan.verified_creators_only,
+        loan.payout,
         &mint.to_account_info(),
         &ctx.accounts.metadata.to_account_info(),
         &mut borrower.to_account_info(),
@@ -94,6 +134,14 @@ pub fn handle_repay_loan<'info>(ctx: Context<'_, '_, '_, 'info, RepayLoan<'info>
     )?;
 
     loan.outstanding = loan.outstanding - payment;
+
+    release_lender_exposure(
+        &ctx.accounts.lender_risk,
+        loan,
+        payment,
+        loan.outstanding == 0,
+        ctx.program_id,
+    )?;
     
     msg!("Repaid {}", payment);
     msg!("Amount outstanding: {}", loan.outstanding);
@@ -5,17 +5,12 @@ use {
         token::{Token, TokenAccount, Mint}
     }
 };
-use crate::state::{Loan, LoanState, TokenManager};
+use crate::state::{Loan, LoanState, LenderRisk, TokenManager};
 use crate::error::{ErrorCodes};
 use crate::utils::*;
-use crate::constants::*;
 
 #[derive(Accounts)]
 pub struct Repossess<'info> {
-    #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
-    )]
-    pub signer: Signer<'info>,
     #[account(mut)]
     pub lender: Signer<'info>,
     /// CHECK: contrained on loan_account
@@ -36,6 +31,7 @@ pub struct Repossess<'info> {
             Loan::PREFIX,
             mint.key().as_ref(),
             borrower.key().as_ref(),
+            &loan.sequence.to_le_bytes(),
         ],
         bump,
         has_one = borrower,
@@ -44,6 +40,16 @@ pub struct Repossess<'info> {
         constraint = loan.state == LoanState::Active,
     )]
     pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            LenderRisk::PREFIX,
+            lender.key().as_ref(),
+        ],
+        bump,
+    )]
+    /// CHECK: deserialized if initialized
+    pub lender_risk: UncheckedAccount<'info>,
     #[account(
         mut,
         seeds = [
@@ -121,6 +127,13 @@ pub fn handle_repossess(ctx: Context<Repossess>) -> Result<()> {
     }
 
     loan.state = LoanState::Defaulted;
+    release_lender_exposure(
+        &ctx.accounts.lender_risk,
+        loan,
+        loan.outstanding,
+        true,
+        ctx.program_id,
+    )?;
     token_manager.accounts.loan = false; 
 
     handle_thaw_and_transfer(
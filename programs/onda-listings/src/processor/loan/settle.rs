@@ -0,0 +1,196 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        program::{invoke_signed},
+        system_instruction::{transfer}
+    }
+};
+use anchor_spl::token::{Mint};
+use crate::state::{Loan, LoanState, LenderRisk, Treasury, GlobalConfig};
+use crate::error::*;
+use crate::utils::*;
+use crate::constants::*;
+
+#[derive(Accounts)]
+pub struct SettleLoanAuction<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    /// CHECK: contrained on loan_account
+    #[account(mut)]
+    pub borrower: AccountInfo<'info>,
+    /// CHECK: contrained on loan_account
+    pub lender: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+            &loan.sequence.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint,
+        has_one = borrower,
+        constraint = loan.state == LoanState::Listed,
+        constraint = loan.lender == Some(lender.key()) @ ErrorCodes::InvalidState,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::AUCTION_VAULT_PREFIX,
+            loan.key().as_ref(),
+        ],
+        bump,
+    )]
+    /// CHECK: seeds
+    pub escrow_payment_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Treasury::PREFIX,
+        ],
+        bump,
+    )]
+    /// CHECK: deserialized if initialized
+    pub treasury: UncheckedAccount<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_settle_loan_auction(ctx: Context<SettleLoanAuction>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let escrow_payment_account = &ctx.accounts.escrow_payment_account;
+
+    require!(loan.is_auction_ended(ctx.accounts.clock.unix_timestamp), ErrorCodes::NotExpired);
+
+    // The winning bid's exposure was recorded when it was placed
+    Loan::set_active(loan, ctx.accounts.clock.unix_timestamp)?;
+
+    // Transfer the winning bid to the borrower
+    let loan_pubkey = loan.key();
+    let signer_bump = &[*ctx.bumps.get("escrow_payment_account").unwrap()];
+    let signer_seeds = &[&[
+        Loan::AUCTION_VAULT_PREFIX,
+        loan_pubkey.as_ref(),
+        signer_bump
+    ][..]];
+
+    // The origination fee is deducted from the amount the borrower receives
+    let origination_fee = get_origination_fee(&ctx.accounts.treasury, &ctx.accounts.global_config, loan.amount.unwrap())?;
+
+    pay_protocol_fee_with_signer(
+        origination_fee,
+        escrow_payment_account.to_account_info(),
+        ctx.accounts.treasury.to_account_info(),
+        signer_seeds
+    )?;
+
+    invoke_signed(
+        &transfer(
+            &escrow_payment_account.key(),
+            &loan.borrower,
+            loan.amount.unwrap() - origination_fee,
+        ),
+        &[
+            escrow_payment_account.to_account_info(),
+            ctx.accounts.borrower.to_account_info(),
+        ],
+        signer_seeds
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelLoanAuction<'info> {
+    pub borrower: Signer<'info>,
+    /// CHECK: contrained on loan_account
+    #[account(mut)]
+    pub lender: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+            &loan.sequence.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint,
+        has_one = borrower,
+        constraint = loan.state == LoanState::Listed,
+        constraint = loan.lender == Some(lender.key()) @ ErrorCodes::InvalidState,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::AUCTION_VAULT_PREFIX,
+            loan.key().as_ref(),
+        ],
+        bump,
+    )]
+    /// CHECK: seeds
+    pub escrow_payment_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            LenderRisk::PREFIX,
+            lender.key().as_ref(),
+        ],
+        bump,
+    )]
+    /// CHECK: deserialized if initialized
+    pub lender_risk: UncheckedAccount<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+// An auction left unsettled past the grace period can be cancelled by the borrower, refunding the
+// winning bid so the listing can be closed
+pub fn handle_cancel_loan_auction(ctx: Context<CancelLoanAuction>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let escrow_payment_account = &ctx.accounts.escrow_payment_account;
+
+    let auction_ends_at = match loan.auction_ends_at {
+        Some(auction_ends_at) => auction_ends_at,
+        None => return err!(ErrorCodes::InvalidState),
+    };
+    let grace_period_ends_at = auction_ends_at
+        .checked_add(AUCTION_SETTLEMENT_GRACE_PERIOD)
+        .ok_or(ErrorCodes::NumericalOverflow)?;
+    require_gt!(ctx.accounts.clock.unix_timestamp, grace_period_ends_at, ErrorCodes::NotExpired);
+
+    let loan_pubkey = loan.key();
+    let signer_bump = &[*ctx.bumps.get("escrow_payment_account").unwrap()];
+    let signer_seeds = &[&[
+        Loan::AUCTION_VAULT_PREFIX,
+        loan_pubkey.as_ref(),
+        signer_bump
+    ][..]];
+
+    invoke_signed(
+        &transfer(
+            &escrow_payment_account.key(),
+            &ctx.accounts.lender.key(),
+            loan.amount.unwrap(),
+        ),
+        &[
+            escrow_payment_account.to_account_info(),
+            ctx.accounts.lender.to_account_info(),
+        ],
+        signer_seeds
+    )?;
+
+    release_lender_exposure(&ctx.accounts.lender_risk, loan, loan.outstanding, true, ctx.program_id)?;
+    loan.exposure_recorded = false;
+    loan.lender = None;
+
+    Ok(())
+}
\ No newline at end of file
@@ -8,18 +8,26 @@ use {
     },
     anchor_spl::token::{Mint, Token, TokenAccount}
 };
-use crate::state::{Loan, LoanOffer, Collection, TokenManager};
+use crate::state::{Loan, LoanOffer, LoanTerms, LenderRisk, Collection, ReferrerStats, TokenManager, Treasury, GlobalConfig};
 use crate::utils::*;
 use crate::error::*;
-use crate::constants::*;
 
 #[derive(Accounts)]
-#[instruction(id: u8)]
+#[instruction(id: u64)]
 pub struct TakeLoanOffer<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
     )]
-    pub signer: Signer<'info>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    /// Only required with an attestation
+    #[account(mut)]
+    /// CHECK: derived and created in assert_attested
+    pub attestation_receipt: Option<UncheckedAccount<'info>>,
     #[account(mut)]
     pub borrower: Signer<'info>,
     #[account(mut)]
@@ -28,9 +36,25 @@ pub struct TakeLoanOffer<'info> {
     #[account(
         mut,
         constraint = deposit_token_account.amount == 1,
+        constraint = deposit_token_account.owner == borrower.key(),
         constraint = deposit_token_account.mint == mint.key(),
     )]
     pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = borrower,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref()
+        ],
+        space = TokenManager::space(),
+        bump,
+        constraint = (
+            token_manager.authority == Some(borrower.key()) ||
+            token_manager.authority == None
+        ) @ ErrorCodes::Unauthorized,
+    )]
+    pub token_manager: Box<Account<'info, TokenManager>>,
     #[account(
         init,
         payer = borrower,
@@ -38,6 +62,7 @@ pub struct TakeLoanOffer<'info> {
             Loan::PREFIX,
             mint.key().as_ref(),
             borrower.key().as_ref(),
+            &token_manager.next_sequence().to_le_bytes(),
         ],
         space = Loan::space(),
         bump,
@@ -49,10 +74,12 @@ pub struct TakeLoanOffer<'info> {
             LoanOffer::PREFIX,
             collection.mint.as_ref(),
             lender.key().as_ref(),
-            &[id],
+            &id.to_le_bytes(),
         ],
         close = lender,
         bump,
+        constraint = loan_offer.mint.is_none() || loan_offer.mint == Some(mint.key()) @ ErrorCodes::InvalidMint,
+        constraint = loan_offer.allows_counterparty(&borrower.key()) @ ErrorCodes::InvalidCounterparty,
     )]
     pub loan_offer: Box<Account<'info, LoanOffer>>,
     #[account(
@@ -66,16 +93,15 @@ pub struct TakeLoanOffer<'info> {
     /// CHECK: seeds
     pub escrow_payment_account: UncheckedAccount<'info>,
     #[account(
-        init_if_needed,
-        payer = borrower,
+        mut,
         seeds = [
-            TokenManager::PREFIX,
-            mint.key().as_ref()
+            LenderRisk::PREFIX,
+            lender.key().as_ref(),
         ],
-        space = TokenManager::space(),
         bump,
     )]
-    pub token_manager: Box<Account<'info, TokenManager>>,
+    /// CHECK: deserialized if initialized
+    pub lender_risk: UncheckedAccount<'info>,
     #[account(
         seeds = [
             Collection::PREFIX,
@@ -84,6 +110,39 @@ pub struct TakeLoanOffer<'info> {
         bump,
     )]
     pub collection: Box<Account<'info, Collection>>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collateral_collection.mint.as_ref(),
+        ],
+        bump,
+        constraint = loan_offer.collections.contains(&collateral_collection.key()) @ ErrorCodes::InvalidCollection,
+        constraint = collateral_collection.config.loan_enabled && global_config.flags.loans_enabled @ ErrorCodes::LoansDisabled,
+    )]
+    pub collateral_collection: Option<Box<Account<'info, Collection>>>,
+    /// The denylist of the collection the collateral is validated against
+    /// CHECK: derived and deserialized if initialized
+    pub denylist: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Treasury::PREFIX,
+        ],
+        bump,
+    )]
+    /// CHECK: deserialized if initialized
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: constrained on referrer_stats
+    pub referrer: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [
+            ReferrerStats::PREFIX,
+            referrer_stats.referrer.as_ref(),
+        ],
+        bump,
+    )]
+    pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
     #[account(constraint = mint.supply == 1)]
     pub mint: Box<Account<'info, Mint>>,
     #[account(mut)]
@@ -111,7 +170,11 @@ pub struct TakeLoanOffer<'info> {
 
 pub fn handle_take_loan_offer(
   ctx: Context<TakeLoanOffer>,
-  _id: u8,
+  _id: u64,
+  proof: Vec<[u8; 32]>,
+  duration: Option<i64>,
+  expected_terms: LoanTerms,
+  collection_proof: Vec<[u8; 32]>,
 ) -> Result<()> {
     let loan = &mut ctx.accounts.loan;
     let offer = &mut ctx.accounts.loan_offer;
@@ -122,7 +185,16 @@ pub fn handle_take_loan_offer(
     let mint = &ctx.accounts.mint;
     let metadata = &ctx.accounts.metadata;
     let edition = &ctx.accounts.edition;
-    let collection = &ctx.accounts.collection;
+    // Tokens from one of the offer's additional collections are validated against that collection
+    let collection = match &ctx.accounts.collateral_collection {
+        Some(collateral_collection) => collateral_collection,
+        None => &ctx.accounts.collection,
+    };
+    // Checked here as well for collateral from the offer's own collection
+    require!(
+        collection.config.loan_enabled && ctx.accounts.global_config.flags.loans_enabled,
+        ErrorCodes::LoansDisabled
+    );
     let deposit_token_account = &mut ctx.accounts.deposit_token_account;
     let token_record = &ctx.accounts.token_record;
     let token_program = &ctx.accounts.token_program;
@@ -131,56 +203,128 @@ pub fn handle_take_loan_offer(
     let authorization_rules_program = &ctx.accounts.authorization_rules_program;
     let authorization_rules = &ctx.accounts.authorization_rules;
 
+    assert_attested(
+        &ctx.accounts.global_config,
+        &ctx.accounts.signer,
+        &ctx.accounts.attestation_receipt,
+        &borrower.to_account_info(),
+        &system_program.to_account_info(),
+        sysvar_instructions,
+        collection.config.permissionless,
+    )?;
+
     assert_collection_valid(
         &metadata,
         mint.key(),
-        collection.key(),
-        ctx.program_id.clone(),
+        collection,
+        &collection_proof,
     )?;
+    assert_mint_not_denied(
+        &ctx.accounts.denylist,
+        &collection.key(),
+        &mint.key(),
+        ctx.program_id,
+    )?;
+
+    if let Some(merkle_root) = offer.merkle_root {
+        assert_merkle_proof_valid(&proof, merkle_root, mint.key())?;
+    }
 
     require_eq!(token_manager.accounts.call_option, false, ErrorCodes::InvalidState);
+    // Positions are no longer unique per PDA, so only one may be open on a mint at a time
+    require_eq!(token_manager.accounts.loan, false, ErrorCodes::InvalidState);
+    require!(!offer.is_expired(ctx.accounts.clock.unix_timestamp), ErrorCodes::OfferExpired);
+
+    let (duration, basis_points) = offer.terms(duration)?;
+    // Batch updated offers are only checked against the collection's limits when taken
+    collection.config.assert_loan_terms_valid(offer.amount.unwrap(), basis_points, duration)?;
+    expected_terms.assert_matches(offer.amount.unwrap(), basis_points, duration)?;
 
     // Init
     loan.mint = mint.key();
     loan.borrower = borrower.key();
     loan.lender = Some(lender.key());
+    loan.collection = collection.key();
     loan.bump = *ctx.bumps.get("loan").unwrap();
     //
+    let creator_basis_points = get_creator_basis_points(
+        &collection.config,
+        collection.config.loan_basis_points,
+        &metadata.to_account_info(),
+    )?;
     Loan::init_ask_state(
         loan,
         offer.amount.unwrap(),
-        collection.config.loan_basis_points,
-        offer.basis_points,
-        offer.duration
+        creator_basis_points,
+        basis_points,
+        duration
     )?;
+    loan.verified_creators_only = collection.config.verified_creators_only;
+    loan.payout = collection.config.royalty_payout();
+    loan.permissionless = collection.config.permissionless;
     Loan::set_active(loan, ctx.accounts.clock.unix_timestamp)?;
+    record_lender_exposure(&ctx.accounts.lender_risk, loan, ctx.program_id)?;
     //
     token_manager.authority = Some(loan.borrower);
     token_manager.accounts.loan = true;
+    token_manager.sequence = token_manager.next_sequence();
+    loan.sequence = token_manager.sequence;
     token_manager.bump = *ctx.bumps.get("token_manager").unwrap();
 
-    handle_delegate_and_freeze(
-        token_manager,
-        borrower.to_account_info(),
-        deposit_token_account.to_account_info(),
-        if let Some(token_record) = token_record {
-            Some(token_record.to_account_info())
-        } else {
-            None
-        },
-        mint.to_account_info(),
-        metadata.to_account_info(),
-        edition.to_account_info(),
-        token_program.to_account_info(),
-        system_program.to_account_info(),
-        sysvar_instructions.to_account_info(),
-        authorization_rules_program.to_account_info(),
-        if let Some(authorization_rules) = authorization_rules {
-            Some(authorization_rules.to_account_info())
-        } else {
-            None
-        },
-    )?;
+    // Freeze deposit token account
+    if deposit_token_account.delegate.is_some() {
+        if deposit_token_account.delegate.unwrap() != token_manager.key() {
+            return err!(ErrorCodes::InvalidState);
+        }
+
+        // An expired listing may have been thawed without revoking the delegate
+        if !deposit_token_account.is_frozen() {
+            handle_freeze(
+                token_manager,
+                borrower.to_account_info(),
+                borrower.to_account_info(),
+                deposit_token_account.to_account_info(),
+                match token_record {
+                    Some(token_record) => Some(token_record.to_account_info()),
+                    None => None,
+                },
+                mint.to_account_info(),
+                metadata.to_account_info(),
+                edition.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                sysvar_instructions.to_account_info(),
+                authorization_rules_program.to_account_info(),
+                match authorization_rules {
+                    Some(authorization_rules) => Some(authorization_rules.to_account_info()),
+                    None => None,
+                }
+            )?;
+        }
+    } else {
+        handle_delegate_and_freeze(
+            token_manager,
+            borrower.to_account_info(),
+            deposit_token_account.to_account_info(),
+            if let Some(token_record) = token_record {
+                Some(token_record.to_account_info())
+            } else {
+                None
+            },
+            mint.to_account_info(),
+            metadata.to_account_info(),
+            edition.to_account_info(),
+            token_program.to_account_info(),
+            system_program.to_account_info(),
+            sysvar_instructions.to_account_info(),
+            authorization_rules_program.to_account_info(),
+            if let Some(authorization_rules) = authorization_rules {
+                Some(authorization_rules.to_account_info())
+            } else {
+                None
+            },
+        )?;
+    }
 
     // Transfer loan amount from offer escrow
     let loan_offer_pubkey = offer.key();
@@ -191,11 +335,37 @@ pub fn handle_take_loan_offer(
         signer_bump
     ][..]];
 
+    // The origination fee is deducted from the amount the borrower receives
+    let origination_fee = get_origination_fee(&ctx.accounts.treasury, &ctx.accounts.global_config, offer.amount.unwrap())?;
+    let referral_fee = get_referral_fee(
+        &ctx.accounts.treasury,
+        &ctx.accounts.global_config,
+        origination_fee,
+        &ctx.accounts.referrer,
+        &mut ctx.accounts.referrer_stats,
+    )?;
+
+    if let Some(referrer_stats) = &ctx.accounts.referrer_stats {
+        pay_protocol_fee_with_signer(
+            referral_fee,
+            escrow_payment_account.to_account_info(),
+            referrer_stats.to_account_info(),
+            signer_seeds
+        )?;
+    }
+
+    pay_protocol_fee_with_signer(
+        origination_fee - referral_fee,
+        escrow_payment_account.to_account_info(),
+        ctx.accounts.treasury.to_account_info(),
+        signer_seeds
+    )?;
+
     invoke_signed(
         &transfer(
             &escrow_payment_account.key(),
             &loan.borrower,
-            offer.amount.unwrap(),
+            offer.amount.unwrap() - origination_fee,
         ),
         &[
             escrow_payment_account.to_account_info(),
@@ -0,0 +1,160 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{Collection, Loan, LoanState, LoanOffer, GlobalConfig};
+use crate::error::{ErrorCodes};
+use crate::utils::*;
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct UpdateLoanOffer<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            LoanOffer::PREFIX,
+            collection.mint.as_ref(),
+            lender.key().as_ref(),
+            &id.to_le_bytes(),
+        ],
+        bump,
+        has_one = lender,
+    )]
+    pub loan_offer: Box<Account<'info, LoanOffer>>,
+    /// CHECK: seeds
+    #[account(
+        mut,
+        seeds=[
+            LoanOffer::VAULT_PREFIX,
+            loan_offer.key().as_ref()
+        ],
+        bump,
+    )]
+    pub escrow_payment_account: AccountInfo<'info>,
+    #[account(
+        seeds = [
+            Collection::PREFIX,
+            collection.mint.as_ref(),
+        ],
+        bump,
+        constraint = signer.is_some() || collection.config.permissionless @ ErrorCodes::Unauthorized,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_update_loan_offer(
+    ctx: Context<UpdateLoanOffer>,
+    _id: u64,
+    amount: u64,
+    basis_points: u16,
+    duration: i64,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    let loan_offer = &mut ctx.accounts.loan_offer;
+
+    if let Some(expires_at) = expires_at {
+        require_gt!(expires_at, ctx.accounts.clock.unix_timestamp, ErrorCodes::InvalidExpiry);
+    }
+
+    LoanOffer::assert_terms_valid(
+        &ctx.accounts.collection.config,
+        amount,
+        basis_points,
+        duration,
+        &loan_offer.rate_curve,
+    )?;
+
+    let loan_offer_pubkey = loan_offer.key();
+    let signer_bump = &[loan_offer.escrow_bump];
+    let signer_seeds = &[&[
+        LoanOffer::VAULT_PREFIX,
+        loan_offer_pubkey.as_ref(),
+        signer_bump
+    ][..]];
+
+    adjust_escrow_balance(
+        ctx.accounts.lender.to_account_info(),
+        ctx.accounts.escrow_payment_account.to_account_info(),
+        signer_seeds,
+        loan_offer.amount.unwrap(),
+        amount,
+    )?;
+
+    loan_offer.amount = Some(amount);
+    loan_offer.basis_points = basis_points;
+    loan_offer.duration = duration;
+    loan_offer.expires_at = expires_at;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateLoan<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Option<Signer<'info>>,
+    pub borrower: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+            &loan.sequence.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint,
+        has_one = borrower,
+        constraint = loan.state == LoanState::Listed @ ErrorCodes::InvalidState,
+        constraint = loan.auction_ends_at.is_none() @ ErrorCodes::InvalidListingType,
+        constraint = signer.is_some() || loan.permissionless @ ErrorCodes::Unauthorized,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        constraint = collection.key() == loan.collection @ ErrorCodes::InvalidCollection,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_update_loan(
+    ctx: Context<UpdateLoan>,
+    amount: u64,
+    basis_points: u16,
+    duration: i64,
+    listing_expires_at: Option<i64>,
+) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let creator_basis_points = loan.creator_basis_points;
+
+    require_gt!(duration, 0, ErrorCodes::InvalidDuration);
+    ctx.accounts.collection.config.assert_loan_terms_valid(amount, basis_points, duration)?;
+
+    if let Some(listing_expires_at) = listing_expires_at {
+        require_gt!(listing_expires_at, ctx.accounts.clock.unix_timestamp, ErrorCodes::InvalidExpiry);
+    }
+
+    Loan::init_ask_state(loan, amount, creator_basis_points, basis_points, duration)?;
+    loan.listing_expires_at = listing_expires_at;
+
+    Ok(())
+}
\ No newline at end of file
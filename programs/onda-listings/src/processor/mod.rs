@@ -1,11 +1,21 @@
 pub mod call_option;
 pub mod collection;
 pub mod common;
+pub mod denylist;
+pub mod global_config;
+pub mod lender_risk;
 // pub mod rental;
 pub mod loan;
+pub mod referrer;
+pub mod treasury;
 
 pub use call_option::*;
 pub use collection::*;
 pub use common::*;
+pub use denylist::*;
+pub use global_config::*;
+pub use lender_risk::*;
 // pub use rental::*;
-pub use loan::*;
\ No newline at end of file
+pub use loan::*;
+pub use referrer::*;
+pub use treasury::*;
\ No newline at end of file
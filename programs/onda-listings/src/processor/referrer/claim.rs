@@ -0,0 +1,36 @@
+use anchor_lang::{prelude::*};
+use crate::state::{ReferrerStats};
+
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            ReferrerStats::PREFIX,
+            referrer.key().as_ref(),
+        ],
+        bump = referrer_stats.bump,
+        has_one = referrer,
+    )]
+    pub referrer_stats: Box<Account<'info, ReferrerStats>>,
+}
+
+pub fn handle_claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+    let referrer_stats = ctx.accounts.referrer_stats.to_account_info();
+    let referrer = ctx.accounts.referrer.to_account_info();
+
+    // Everything above the rent-exempt minimum is accrued fees
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(referrer_stats.data_len());
+    let fees = referrer_stats.lamports().saturating_sub(rent_exempt_minimum);
+
+    if fees > 0 {
+        msg!("Claiming {} lamports in referral fees", fees);
+
+        **referrer_stats.try_borrow_mut_lamports()? -= fees;
+        **referrer.try_borrow_mut_lamports()? += fees;
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,40 @@
+use anchor_lang::{prelude::*};
+use crate::state::{ReferrerStats, GlobalConfig};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct InitReferrerStats<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    #[account(
+        init,
+        seeds = [
+            ReferrerStats::PREFIX,
+            referrer.key().as_ref(),
+        ],
+        bump,
+        payer = referrer,
+        space = ReferrerStats::space(),
+    )]
+    pub referrer_stats: Box<Account<'info, ReferrerStats>>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_init_referrer_stats(ctx: Context<InitReferrerStats>) -> Result<()> {
+    let referrer_stats = &mut ctx.accounts.referrer_stats;
+
+    referrer_stats.referrer = ctx.accounts.referrer.key();
+    referrer_stats.bump = *ctx.bumps.get("referrer_stats").unwrap();
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+pub mod claim;
+pub mod initialize;
+
+pub use claim::*;
+pub use initialize::*;
\ No newline at end of file
@@ -1,9 +1,8 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{Rental, RentalState, Collection, TokenManager};
+use crate::state::{Rental, RentalState, Collection, TokenManager, GlobalConfig};
 use crate::error::{ErrorCodes};
 use crate::utils::*;
-use crate::constants::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct RentalArgs {
@@ -15,7 +14,12 @@ pub struct RentalArgs {
 #[derive(Accounts)]
 pub struct InitRental<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
     )]
     pub signer: Signer<'info>,
     #[account(mut)]
@@ -59,6 +63,8 @@ pub struct InitRental<'info> {
         constraint = collection.config.rental_enabled == true
     )]
     pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: derived and deserialized if initialized
+    pub denylist: UncheckedAccount<'info>,
     #[account(constraint = mint.supply == 1)]
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: deserialized and checked
@@ -87,8 +93,15 @@ pub fn handle_init_rental(
     assert_collection_valid(
         &ctx.accounts.metadata,
         ctx.accounts.mint.key(),
-        ctx.accounts.collection.key(),
-        ctx.program_id.clone(),
+        &ctx.accounts.collection,
+        // Rentals take no proof, so merkle root collections cannot be rented
+        &Vec::new(),
+    )?;
+    assert_mint_not_denied(
+        &ctx.accounts.denylist,
+        &ctx.accounts.collection.key(),
+        &ctx.accounts.mint.key(),
+        ctx.program_id,
     )?;
 
     if unix_timestamp > args.expiry {
@@ -105,7 +118,13 @@ pub fn handle_init_rental(
     rental.bump = *ctx.bumps.get("rental").unwrap();
     //
     rental.amount = args.amount;
-    rental.creator_basis_points = collection.config.rental_basis_points;
+    rental.creator_basis_points = get_creator_basis_points(
+        &collection.config,
+        collection.config.rental_basis_points,
+        &ctx.accounts.metadata.to_account_info(),
+    )?;
+    rental.verified_creators_only = collection.config.verified_creators_only;
+    rental.payout = collection.config.royalty_payout();
     rental.escrow_balance = 0;
     rental.expiry = args.expiry;
     rental.state = RentalState::Listed;
@@ -1,6 +1,6 @@
 use anchor_lang::{system_program,prelude::*};
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{Rental, RentalState, TokenManager};
+use crate::state::{Rental, RentalState, TokenManager, GlobalConfig};
 use crate::error::{ErrorCodes};
 use crate::constants::*;
 use crate::utils::*;
@@ -9,7 +9,12 @@ use crate::utils::*;
 #[instruction(days: u16)]
 pub struct TakeRental <'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
     )]
     pub signer: Signer<'info>,
     #[account(mut)]
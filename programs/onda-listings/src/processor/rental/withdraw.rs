@@ -1,13 +1,18 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token};
-use crate::state::{Rental, Collection};
+use crate::state::{Rental, Collection, GlobalConfig};
+use crate::error::*;
 use crate::utils::*;
-use crate::constants::*;
 
 #[derive(Accounts)]
 pub struct WithdrawFromRentalEscrow<'info> {
     #[account(
-        constraint = signer.key() == SIGNER_PUBKEY
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
     )]
     pub signer: Signer<'info>,
     /// CHECK: contrained on listing_account
@@ -0,0 +1,41 @@
+use anchor_lang::{prelude::*};
+use crate::state::{GlobalConfig, Treasury};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct InitTreasury<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = authority.key() == global_config.admin @ ErrorCodes::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        seeds = [
+            Treasury::PREFIX,
+        ],
+        bump,
+        payer = authority,
+        space = Treasury::space(),
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_init_treasury(ctx: Context<InitTreasury>) -> Result<()> {
+    let treasury = &mut ctx.accounts.treasury;
+
+    treasury.bump = *ctx.bumps.get("treasury").unwrap();
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+pub mod initialize;
+pub mod withdraw;
+
+pub use initialize::*;
+pub use withdraw::*;
\ No newline at end of file
@@ -0,0 +1,50 @@
+use anchor_lang::{prelude::*};
+use crate::state::{GlobalConfig, Treasury};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct WithdrawFromTreasury<'info> {
+    #[account(
+        seeds = [GlobalConfig::PREFIX],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        constraint = global_config.is_signer(&signer.key()) @ ErrorCodes::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = authority.key() == global_config.admin @ ErrorCodes::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Treasury::PREFIX,
+        ],
+        bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_withdraw_from_treasury(
+    ctx: Context<WithdrawFromTreasury>,
+    amount: u64,
+) -> Result<()> {
+    let treasury = &ctx.accounts.treasury.to_account_info();
+    let authority = &ctx.accounts.authority.to_account_info();
+
+    // The treasury must stay rent exempt
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury.data_len());
+    let available = treasury.lamports().saturating_sub(rent_exempt_minimum);
+    require_gte!(available, amount, ErrorCodes::InvalidEscrowBalance);
+
+    msg!("Withdrawing {} lamports from treasury", amount);
+
+    **treasury.try_borrow_mut_lamports()? -= amount;
+    **authority.try_borrow_mut_lamports()? += amount;
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    hash::hashv,
+    instruction::Instruction,
+};
+
+/// Signed by one of the global config's signers in place of co-signing the transaction
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct Attestation {
+    /// Hash of the attested instruction's program, accounts and data
+    pub instruction_hash: [u8; 32],
+    /// The attestation can't be used after this timestamp
+    pub expiry: i64,
+    /// Chosen by the attesting signer, the attestation's receipt is derived from it
+    pub nonce: u64,
+}
+
+/// Created when an attestation is used so that it can't be replayed
+#[account]
+pub struct AttestationReceipt {
+    /// Paid for the receipt, and is refunded when it's closed
+    pub payer: Pubkey,
+    /// Copied from the attestation, the receipt can be closed once it has passed
+    pub expiry: i64,
+    /// misc
+    pub bump: u8,
+}
+
+impl AttestationReceipt {
+    pub const PREFIX: &'static [u8] = b"attestation_receipt";
+
+    pub fn space() -> usize {
+        8 + // key
+        32 + // payer
+        8 + // expiry
+        1 // bump
+    }
+}
+
+impl Attestation {
+    pub fn hash_instruction(instruction: &Instruction) -> [u8; 32] {
+        let mut values: Vec<&[u8]> = vec![instruction.program_id.as_ref()];
+
+        for account in instruction.accounts.iter() {
+            values.push(account.pubkey.as_ref());
+        }
+
+        values.push(&instruction.data);
+
+        hashv(&values).to_bytes()
+    }
+}
\ No newline at end of file
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::constants::*;
 use crate::error::*;
+use crate::state::{Config, Payout};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
 pub enum CallOptionState {
@@ -9,6 +10,23 @@ pub enum CallOptionState {
     Exercised,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct CallOptionTerms {
+    pub amount: u64,
+    pub strike_price: u64,
+    pub expiry: i64,
+}
+
+impl CallOptionTerms {
+    pub fn assert_matches(&self, amount: u64, strike_price: u64, expiry: i64) -> Result<()> {
+        require_eq!(self.amount, amount, ErrorCodes::TermsMismatch);
+        require_eq!(self.strike_price, strike_price, ErrorCodes::TermsMismatch);
+        require_eq!(self.expiry, expiry, ErrorCodes::TermsMismatch);
+
+        Ok(())
+    }
+}
+
 #[account]
 pub struct CallOption {
     /// Whether the option is active
@@ -17,6 +35,14 @@ pub struct CallOption {
     pub amount: u64,
     /// The creator fee
     pub creator_basis_points: u16,
+    /// The creator fee on the strike price when exercised
+    pub exercise_basis_points: u16,
+    /// Whether royalties are only paid to verified creators
+    pub verified_creators_only: bool,
+    /// (Optional) The collection authority's share of the creator fee
+    pub payout: Option<Payout>,
+    /// Whether the option can be managed without the co-signer
+    pub permissionless: bool,
     /// The issuer of the call option
     pub seller: Pubkey,
     /// The buyer of the call option
@@ -29,6 +55,12 @@ pub struct CallOption {
     pub mint: Pubkey,
     /// (Optional) The mint of the spl-token mint
     pub token_mint: Option<Pubkey>,
+    /// (Optional) The timestamp after which the listing can no longer be bought
+    pub listing_expires_at: Option<i64>,
+    /// Buyers allowed to buy the option, anyone if empty
+    pub counterparties: Vec<Pubkey>,
+    /// Position of the option in the sequence of loans and call options on its mint
+    pub sequence: u64,
     /// Misc
     pub bump: u8,
 }
@@ -71,21 +103,39 @@ impl CallOption {
         1 + // state
         8 + // amount
         2 + // create_basis_points
+        2 + // exercise_basis_points
+        1 + // verified_creators_only
+        (1 + 32 + 2) + // payout
+        1 + // permissionless
         32 + // seller
         1 + 32 + // buyer
         8 + // expiry
         8 + // strike price
         32 + // mint
-        32 + // token mint
+        (1 + 32) + // token mint
+        (1 + 8) + // listing_expires_at
+        (4 + 32 * MAX_COUNTERPARTIES) + // counterparties
+        8 + // sequence
         1 // bump
     }
 
+    pub fn is_listing_expired(&self, unix_timestamp: i64) -> bool {
+        match self.listing_expires_at {
+            Some(listing_expires_at) => unix_timestamp > listing_expires_at,
+            None => false,
+        }
+    }
+
+    pub fn allows_counterparty(&self, counterparty: &Pubkey) -> bool {
+        self.counterparties.is_empty() || self.counterparties.contains(counterparty)
+    }
+
     pub const PREFIX: &'static [u8] = b"call_option";
 }
 
 #[account]
 pub struct CallOptionBid {
-    pub id: u8,
+    pub id: u64,
     /// The buyer making the offer
     pub buyer: Pubkey,
     /// Duration of the loan in seconds
@@ -96,24 +146,150 @@ pub struct CallOptionBid {
     pub amount: u64,
     /// The collection
     pub collection: Pubkey,
+    /// (Optional) The timestamp after which the bid can no longer be sold into
+    pub expires_at: Option<i64>,
+    /// (Optional) The only mint that may be sold into the bid
+    pub mint: Option<Pubkey>,
+    /// (Optional) Merkle root of the mints that may be sold into the bid
+    pub merkle_root: Option<[u8; 32]>,
+    /// Sellers allowed to sell into the bid, anyone if empty
+    pub counterparties: Vec<Pubkey>,
     /// misc
     pub bump: u8,
     pub escrow_bump: u8,
 }
 
 impl CallOptionBid {
+    pub fn assert_terms_valid(config: &Config, expiry: i64, unix_timestamp: i64) -> Result<()> {
+        require_gt!(expiry, unix_timestamp, ErrorCodes::InvalidExpiry);
+        config.assert_option_expiry_valid(expiry, unix_timestamp)
+    }
+
     pub fn space() -> usize {
         8 + // key
-        1 + // id
+        8 + // id
         32 + // buyer
         8 + // expiry
         8 + // strike_price
         8 + // amount
         32 + // collection
+        (1 + 8) + // expires_at
+        (1 + 32) + // mint
+        (1 + 32) + // merkle_root
+        (4 + 32 * MAX_COUNTERPARTIES) + // counterparties
         1 + // bump
         1 // escrow_bump
     }
 
+    pub fn is_expired(&self, unix_timestamp: i64) -> bool {
+        match self.expires_at {
+            Some(expires_at) => unix_timestamp > expires_at,
+            None => false,
+        }
+    }
+
+    pub fn allows_counterparty(&self, counterparty: &Pubkey) -> bool {
+        self.counterparties.is_empty() || self.counterparties.contains(counterparty)
+    }
+
     pub const PREFIX: &'static [u8] = b"call_option_bid";
     pub const VAULT_PREFIX: &'static [u8] = b"call_option_bid_vault";
+}
+
+/// Layout of call options created before they were sequenced, keyed by mint and seller only
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyCallOption {
+    pub state: CallOptionState,
+    pub amount: u64,
+    pub creator_basis_points: u16,
+    pub seller: Pubkey,
+    pub buyer: Option<Pubkey>,
+    pub expiry: i64,
+    pub strike_price: u64,
+    pub mint: Pubkey,
+    pub token_mint: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl LegacyCallOption {
+    pub fn space() -> usize {
+        8 + // key
+        1 + // state
+        8 + // amount
+        2 + // create_basis_points
+        32 + // seller
+        1 + 32 + // buyer
+        8 + // expiry
+        8 + // strike price
+        32 + // mint
+        32 + // token mint
+        1 // bump
+    }
+
+    // Legacy options are the first position on their mint and pay the full seller fee when exercised
+    pub fn migrate(&self, exercise_basis_points: u16, bump: u8) -> CallOption {
+        CallOption {
+            state: self.state,
+            amount: self.amount,
+            creator_basis_points: self.creator_basis_points,
+            exercise_basis_points,
+            verified_creators_only: false,
+            payout: None,
+            permissionless: false,
+            seller: self.seller,
+            buyer: self.buyer,
+            expiry: self.expiry,
+            strike_price: self.strike_price,
+            mint: self.mint,
+            token_mint: self.token_mint,
+            listing_expires_at: None,
+            counterparties: vec![],
+            sequence: 0,
+            bump,
+        }
+    }
+}
+
+/// Layout of bids created before ids were counted per buyer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyCallOptionBid {
+    pub id: u8,
+    pub buyer: Pubkey,
+    pub expiry: i64,
+    pub strike_price: u64,
+    pub amount: u64,
+    pub collection: Pubkey,
+    pub bump: u8,
+    pub escrow_bump: u8,
+}
+
+impl LegacyCallOptionBid {
+    pub fn space() -> usize {
+        8 + // key
+        1 + // id
+        32 + // buyer
+        8 + // expiry
+        8 + // strike_price
+        8 + // amount
+        32 + // collection
+        1 + // bump
+        1 // escrow_bump
+    }
+
+    pub fn migrate(&self, id: u64, bump: u8, escrow_bump: u8) -> CallOptionBid {
+        CallOptionBid {
+            id,
+            buyer: self.buyer,
+            expiry: self.expiry,
+            strike_price: self.strike_price,
+            amount: self.amount,
+            collection: self.collection,
+            expires_at: None,
+            mint: None,
+            merkle_root: None,
+            counterparties: vec![],
+            bump,
+            escrow_bump,
+        }
+    }
 }
\ No newline at end of file
@@ -1,14 +1,49 @@
 use anchor_lang::prelude::*;
+use crate::error::*;
 
 #[account]
 pub struct Collection {
+    /// Manages the collection config and denylist. The protocol fee and payout are
+    /// reserved to the global config admin
     pub authority: Pubkey,
+    /// The collection mint, first verified creator or merkle root the collection is keyed by
     pub mint: Pubkey,
+    pub collection_type: CollectionType,
     pub config: Config,
+    /// Kept at its original size, fields added since the legacy layout are migrated in
     pub reserved: [u8; 64],
     pub bump: u8,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub enum CollectionType {
+    /// Tokens verified in a Metaplex Certified Collection
+    Certified,
+    /// Tokens whose first verified creator is the collection's key
+    Creator,
+    /// Tokens whose mint is a leaf of the merkle root stored as the collection's key
+    MerkleRoot,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub enum RoyaltyPolicy {
+    /// Pays the basis points configured for each instrument, as collections did before
+    /// the policy existed. Kept first so that it decodes from zeroed bytes.
+    Fixed,
+    /// Pays the seller fee set in the token's metadata
+    Full,
+    /// Pays no royalties
+    None,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct Payout {
+    /// The collection authority's payout address
+    pub address: Pubkey,
+    /// Share of the instrument fee paid to the address instead of the creators
+    pub share_basis_points: u16,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
 pub struct Config {
     pub loan_enabled: bool,
@@ -17,6 +52,97 @@ pub struct Config {
     pub option_basis_points: u16,
     pub rental_enabled: bool,
     pub rental_basis_points: u16, 
+    /// (Optional) Overrides the treasury's protocol fee for the collection
+    pub protocol_fee_basis_points: Option<u16>,
+    /// How creator royalties are charged on every instrument
+    pub royalty_policy: RoyaltyPolicy,
+    /// Only pay royalties to verified creators
+    pub verified_creators_only: bool,
+    /// (Optional) Routes a share of the instrument fees to the collection authority
+    pub payout: Option<Payout>,
+    /// (Optional) The smallest amount a loan may be listed or offered for
+    pub min_loan_amount: Option<u64>,
+    /// (Optional) The largest amount a loan may be listed or offered for
+    pub max_loan_amount: Option<u64>,
+    /// (Optional) The longest duration of a loan in seconds
+    pub max_loan_duration: Option<i64>,
+    /// (Optional) The highest annual percentage yield of a loan
+    pub max_loan_basis_points: Option<u16>,
+    /// (Optional) The furthest an option may expire, in seconds from when it is listed
+    pub max_option_expiry: Option<i64>,
+    /// Loans and options in the collection don't need the co-signer, leaving the on-chain checks as the only guard
+    pub permissionless: bool,
+}
+
+impl Config {
+    pub fn creator_basis_points(&self, basis_points: u16, seller_fee_basis_points: u16) -> u16 {
+        match self.royalty_policy {
+            RoyaltyPolicy::Full => seller_fee_basis_points,
+            RoyaltyPolicy::Fixed => basis_points,
+            RoyaltyPolicy::None => 0,
+        }
+    }
+
+    // Exercising buys the token at the strike price, so the metadata royalties are paid
+    // unless the collection waives them
+    pub fn exercise_basis_points(&self, seller_fee_basis_points: u16) -> u16 {
+        match self.royalty_policy {
+            RoyaltyPolicy::None => 0,
+            _ => seller_fee_basis_points,
+        }
+    }
+
+    // Only the configured instrument fees are shared, metadata royalties go to the creators in full
+    pub fn royalty_payout(&self) -> Option<Payout> {
+        match self.royalty_policy {
+            RoyaltyPolicy::Fixed => self.payout,
+            _ => None,
+        }
+    }
+
+    pub fn assert_valid(&self) -> Result<()> {
+        if let Some(protocol_fee_basis_points) = self.protocol_fee_basis_points {
+            require_gte!(10_000, protocol_fee_basis_points, ErrorCodes::InvalidBasisPoints);
+        }
+
+        if let Some(payout) = self.payout {
+            require_gte!(10_000, payout.share_basis_points, ErrorCodes::InvalidBasisPoints);
+        }
+
+        Ok(())
+    }
+
+    pub fn assert_loan_terms_valid(&self, amount: u64, basis_points: u16, duration: i64) -> Result<()> {
+        if let Some(min_loan_amount) = self.min_loan_amount {
+            require_gte!(amount, min_loan_amount, ErrorCodes::TermsOutOfBounds);
+        }
+
+        if let Some(max_loan_amount) = self.max_loan_amount {
+            require_gte!(max_loan_amount, amount, ErrorCodes::TermsOutOfBounds);
+        }
+
+        if let Some(max_loan_duration) = self.max_loan_duration {
+            require_gte!(max_loan_duration, duration, ErrorCodes::TermsOutOfBounds);
+        }
+
+        if let Some(max_loan_basis_points) = self.max_loan_basis_points {
+            require_gte!(max_loan_basis_points, basis_points, ErrorCodes::TermsOutOfBounds);
+        }
+
+        Ok(())
+    }
+
+    pub fn assert_option_expiry_valid(&self, expiry: i64, unix_timestamp: i64) -> Result<()> {
+        if let Some(max_option_expiry) = self.max_option_expiry {
+            let latest_expiry = unix_timestamp
+                .checked_add(max_option_expiry)
+                .ok_or(ErrorCodes::NumericalOverflow)?;
+
+            require_gte!(latest_expiry, expiry, ErrorCodes::TermsOutOfBounds);
+        }
+
+        Ok(())
+    }
 }
 
 impl Collection {
@@ -24,10 +150,73 @@ impl Collection {
         8 +
         32 + // authority
         32 + // collection
-        1 + 2 + 1 + 2 + 1 + 2 + // config
+        1 + // collection_type
+        1 + 2 + 1 + 2 + 1 + 2 + (1 + 2) + 1 + 1 + (1 + 32 + 2) + // config
+        (1 + 8) + (1 + 8) + (1 + 8) + (1 + 2) + (1 + 8) + // config limits
+        1 + // config permissionless
         64 + // reserved
         1 // bump
     }
 
     pub const PREFIX: &'static [u8] = b"collection";
+}
+
+/// Layout of collections created before protocol fees, royalty policies and limits were configurable
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct LegacyCollection {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub config: LegacyConfig,
+    pub reserved: [u8; 64],
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct LegacyConfig {
+    pub loan_enabled: bool,
+    pub loan_basis_points: u16,
+    pub option_enabled: bool,
+    pub option_basis_points: u16,
+    pub rental_enabled: bool,
+    pub rental_basis_points: u16,
+}
+
+impl LegacyCollection {
+    pub fn space() -> usize {
+        8 +
+        32 + // authority
+        32 + // collection
+        1 + 2 + 1 + 2 + 1 + 2 + // config
+        64 + // reserved
+        1 // bump
+    }
+
+    // Settings added since default to the behaviour the collection had before
+    pub fn migrate(&self) -> Collection {
+        Collection {
+            authority: self.authority,
+            mint: self.mint,
+            collection_type: CollectionType::Certified,
+            config: Config {
+                loan_enabled: self.config.loan_enabled,
+                loan_basis_points: self.config.loan_basis_points,
+                option_enabled: self.config.option_enabled,
+                option_basis_points: self.config.option_basis_points,
+                rental_enabled: self.config.rental_enabled,
+                rental_basis_points: self.config.rental_basis_points,
+                protocol_fee_basis_points: None,
+                royalty_policy: RoyaltyPolicy::Fixed,
+                verified_creators_only: false,
+                payout: None,
+                min_loan_amount: None,
+                max_loan_amount: None,
+                max_loan_duration: None,
+                max_loan_basis_points: None,
+                max_option_expiry: None,
+                permissionless: false,
+            },
+            reserved: [0; 64],
+            bump: self.bump,
+        }
+    }
 }
\ No newline at end of file
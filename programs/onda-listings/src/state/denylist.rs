@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::error::*;
+
+#[account]
+pub struct Denylist {
+    /// The collection the denylist applies to
+    pub collection: Pubkey,
+    /// Mints that may not be used in the collection, kept sorted
+    pub mints: Vec<Pubkey>,
+    /// misc
+    pub bump: u8,
+}
+
+impl Denylist {
+    pub fn space() -> usize {
+        8 + // key
+        32 + // collection
+        (4 + 32 * MAX_DENYLIST_MINTS) + // mints
+        1 // bump
+    }
+
+    pub fn is_denied(&self, mint: &Pubkey) -> bool {
+        self.mints.binary_search(mint).is_ok()
+    }
+
+    pub fn deny(&mut self, mint: Pubkey) -> Result<()> {
+        if let Err(index) = self.mints.binary_search(&mint) {
+            require_gt!(MAX_DENYLIST_MINTS, self.mints.len(), ErrorCodes::DenylistFull);
+            self.mints.insert(index, mint);
+        }
+
+        Ok(())
+    }
+
+    pub fn allow(&mut self, mint: &Pubkey) {
+        if let Ok(index) = self.mints.binary_search(mint) {
+            self.mints.remove(index);
+        }
+    }
+
+    pub const PREFIX: &'static [u8] = b"denylist";
+}
\ No newline at end of file
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::error::*;
+use crate::state::Config;
+
+#[account]
+pub struct GlobalConfig {
+    /// May update the global config, initialize collections and withdraw from the treasury
+    pub admin: Pubkey,
+    /// Keys that may co-sign instructions
+    pub signers: Vec<Pubkey>,
+    /// The fees charged by the protocol
+    pub fees: ProtocolFees,
+    /// Switches that apply across every collection
+    pub flags: GlobalFlags,
+    /// misc
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct ProtocolFees {
+    /// Charged on loan interest and option premiums, unless overridden by the collection
+    pub fee_basis_points: u16,
+    /// Charged on loan principal when a loan is originated
+    pub origination_fee_basis_points: u16,
+    /// Share of the protocol fee paid to the referrer, if there is one
+    pub referral_share_basis_points: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct GlobalFlags {
+    /// Whether new loans may be listed, offered or taken
+    pub loans_enabled: bool,
+    /// Whether new call options may be listed, bid on or sold
+    pub options_enabled: bool,
+}
+
+impl ProtocolFees {
+    pub fn assert_valid(&self) -> Result<()> {
+        require_gte!(10_000, self.fee_basis_points, ErrorCodes::InvalidBasisPoints);
+        require_gte!(10_000, self.origination_fee_basis_points, ErrorCodes::InvalidBasisPoints);
+        require_gte!(10_000, self.referral_share_basis_points, ErrorCodes::InvalidBasisPoints);
+
+        Ok(())
+    }
+}
+
+impl GlobalConfig {
+    pub fn space() -> usize {
+        8 + // key
+        32 + // admin
+        (4 + 32 * MAX_SIGNERS) + // signers
+        2 + 2 + 2 + // fees
+        1 + 1 + // flags
+        1 // bump
+    }
+
+    pub fn assert_signers_valid(signers: &Vec<Pubkey>) -> Result<()> {
+        require_gt!(signers.len(), 0, ErrorCodes::InvalidSigners);
+        require_gte!(MAX_SIGNERS, signers.len(), ErrorCodes::InvalidSigners);
+
+        Ok(())
+    }
+
+    pub fn is_signer(&self, key: &Pubkey) -> bool {
+        self.signers.contains(key)
+    }
+
+    pub fn fee_basis_points(&self, config: &Config) -> u16 {
+        config.protocol_fee_basis_points.unwrap_or(self.fees.fee_basis_points)
+    }
+
+    pub const PREFIX: &'static [u8] = b"global_config";
+}
\ No newline at end of file
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::error::*;
+
+#[account]
+pub struct LenderRisk {
+    /// The lender the limits apply to
+    pub lender: Pubkey,
+    /// The limits set by the lender
+    pub limits: RiskLimits,
+    /// Total principal outstanding across active loans
+    pub outstanding: u64,
+    /// Number of active loans
+    pub active_loans: u32,
+    /// Principal outstanding per collection
+    pub exposures: Vec<CollectionExposure>,
+    /// misc
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct RiskLimits {
+    /// (Optional) Maximum total principal outstanding
+    pub max_outstanding: Option<u64>,
+    /// (Optional) Maximum principal outstanding in a single collection
+    pub max_collection_outstanding: Option<u64>,
+    /// (Optional) Maximum number of active loans
+    pub max_active_loans: Option<u32>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct CollectionExposure {
+    pub collection: Pubkey,
+    pub outstanding: u64,
+}
+
+impl LenderRisk {
+    pub fn space() -> usize {
+        8 + // key
+        32 + // lender
+        (1 + 8) + (1 + 8) + (1 + 4) + // limits
+        8 + // outstanding
+        4 + // active_loans
+        (4 + (32 + 8) * MAX_RISK_COLLECTIONS) + // exposures
+        1 // bump
+    }
+
+    pub fn assert_exposure_allowed(&self, collection: &Pubkey, amount: u64) -> Result<()> {
+        let outstanding = self.outstanding.checked_add(amount).ok_or(ErrorCodes::NumericalOverflow)?;
+        let active_loans = self.active_loans.checked_add(1).ok_or(ErrorCodes::NumericalOverflow)?;
+
+        let collection_outstanding = match self.exposures.iter().find(|e| e.collection == *collection) {
+            Some(exposure) => exposure.outstanding.checked_add(amount).ok_or(ErrorCodes::NumericalOverflow)?,
+            None => {
+                require_gt!(MAX_RISK_COLLECTIONS, self.exposures.len(), ErrorCodes::TooManyCollections);
+                amount
+            }
+        };
+
+        if let Some(max_collection_outstanding) = self.limits.max_collection_outstanding {
+            require_gte!(max_collection_outstanding, collection_outstanding, ErrorCodes::ExposureLimitExceeded);
+        }
+
+        if let Some(max_outstanding) = self.limits.max_outstanding {
+            require_gte!(max_outstanding, outstanding, ErrorCodes::ExposureLimitExceeded);
+        }
+
+        if let Some(max_active_loans) = self.limits.max_active_loans {
+            require_gte!(max_active_loans, active_loans, ErrorCodes::ExposureLimitExceeded);
+        }
+
+        Ok(())
+    }
+
+    pub fn add_exposure(&mut self, collection: Pubkey, amount: u64) -> Result<()> {
+        self.assert_exposure_allowed(&collection, amount)?;
+
+        self.outstanding += amount;
+        self.active_loans += 1;
+
+        match self.exposures.iter_mut().find(|e| e.collection == collection) {
+            Some(exposure) => exposure.outstanding += amount,
+            None => self.exposures.push(CollectionExposure { collection, outstanding: amount }),
+        };
+
+        Ok(())
+    }
+
+    pub fn remove_exposure(&mut self, collection: Pubkey, amount: u64, settled: bool) -> Result<()> {
+        self.outstanding = self.outstanding.saturating_sub(amount);
+
+        if settled {
+            self.active_loans = self.active_loans.saturating_sub(1);
+        }
+
+        if let Some(index) = self.exposures.iter().position(|e| e.collection == collection) {
+            let outstanding = self.exposures[index].outstanding.saturating_sub(amount);
+
+            if outstanding == 0 {
+                self.exposures.remove(index);
+            } else {
+                self.exposures[index].outstanding = outstanding;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub const PREFIX: &'static [u8] = b"lender_risk";
+}
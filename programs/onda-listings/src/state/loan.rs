@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::constants::*;
 use crate::error::*;
+use crate::state::{Config, Payout};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
 pub enum LoanState {
@@ -25,6 +26,12 @@ pub struct Loan {
     pub basis_points: u16,
     /// The creator fee
     pub creator_basis_points: u16,
+    /// Whether royalties are only paid to verified creators
+    pub verified_creators_only: bool,
+    /// (Optional) The collection authority's share of the creator fee
+    pub payout: Option<Payout>,
+    /// Whether the loan can be managed without the co-signer
+    pub permissionless: bool,
     /// The amount outstanding
     pub outstanding: u64,
     /// The liquidation threshold in basis points
@@ -39,6 +46,18 @@ pub struct Loan {
     pub mint: Pubkey,
     /// The mint of the spl-token mint
     pub token_mint: Option<Pubkey>,
+    /// (Optional) The timestamp after which the listing can no longer be given
+    pub listing_expires_at: Option<i64>,
+    /// Lenders allowed to give the loan, anyone if empty
+    pub counterparties: Vec<Pubkey>,
+    /// The collection the collateral belongs to
+    pub collection: Pubkey,
+    /// Whether the loan counts towards the lender's risk account
+    pub exposure_recorded: bool,
+    /// (Optional) The end of the bidding period when lenders compete on rate
+    pub auction_ends_at: Option<i64>,
+    /// Position of the loan in the sequence of loans and call options on its mint
+    pub sequence: u64,
     /// misc
     pub bump: u8,
 }
@@ -90,6 +109,9 @@ impl Loan {
         (1 + 8) + // amount
         2 + // basis_points
         2 + // creator_basis_points
+        1 + // verified_creators_only
+        (1 + 32 + 2) + // payout
+        1 + // permissionless
         8 + // outstanding
         (1 + 4) + // threshold
         1 + // installments
@@ -99,17 +121,67 @@ impl Loan {
         (1 + 8) + // start_date
         32 + // mint
         (1 + 32) + // token_mint
+        (1 + 8) + // listing_expires_at
+        (4 + 32 * MAX_COUNTERPARTIES) + // counterparties
+        32 + // collection
+        1 + // exposure_recorded
+        (1 + 8) + // auction_ends_at
+        8 + // sequence
         1 // bump
     }
 
+    pub fn is_listing_expired(&self, unix_timestamp: i64) -> bool {
+        match self.listing_expires_at {
+            Some(listing_expires_at) => unix_timestamp > listing_expires_at,
+            None => false,
+        }
+    }
+
+    pub fn allows_counterparty(&self, counterparty: &Pubkey) -> bool {
+        self.counterparties.is_empty() || self.counterparties.contains(counterparty)
+    }
+
+    pub fn is_auction_ended(&self, unix_timestamp: i64) -> bool {
+        match self.auction_ends_at {
+            Some(auction_ends_at) => unix_timestamp > auction_ends_at,
+            None => false,
+        }
+    }
+
     pub const PREFIX: &'static [u8] = b"loan";
+    pub const AUCTION_VAULT_PREFIX: &'static [u8] = b"loan_auction_vault";
+}
+
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct LoanTerms {
+    pub amount: u64,
+    pub basis_points: u16,
+    pub duration: i64,
 }
 
+impl LoanTerms {
+    pub fn assert_matches(&self, amount: u64, basis_points: u16, duration: i64) -> Result<()> {
+        require_eq!(self.amount, amount, ErrorCodes::TermsMismatch);
+        require_eq!(self.basis_points, basis_points, ErrorCodes::TermsMismatch);
+        require_eq!(self.duration, duration, ErrorCodes::TermsMismatch);
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Debug)]
+pub struct RatePoint {
+    /// Duration of the loan in seconds
+    pub duration: i64,
+    /// Annual percentage yield at this duration
+    pub basis_points: u16,
+}
 
 #[account]
 pub struct LoanOffer {
     /// id of the offer
-    pub id: u8, 
+    pub id: u64,
     /// The lender making the offer
     pub lender: Pubkey,
     /// The amount of the loan
@@ -124,6 +196,18 @@ pub struct LoanOffer {
     pub ltv: Option<u32>,
     /// The liquidation threshold in basis points
     pub threshold: Option<u32>,
+    /// (Optional) The timestamp after which the offer can no longer be taken
+    pub expires_at: Option<i64>,
+    /// (Optional) The only mint that may be used to take the offer
+    pub mint: Option<Pubkey>,
+    /// (Optional) Merkle root of the mints that may be used to take the offer
+    pub merkle_root: Option<[u8; 32]>,
+    /// Additional collections whose tokens may be used to take the offer
+    pub collections: Vec<Pubkey>,
+    /// Borrowers allowed to take the offer, anyone if empty
+    pub counterparties: Vec<Pubkey>,
+    /// (Optional) Durations and rates the borrower can choose between, fixed terms if empty
+    pub rate_curve: Vec<RatePoint>,
     /// misc
     pub bump: u8,
     pub escrow_bump: u8,
@@ -132,7 +216,7 @@ pub struct LoanOffer {
 impl LoanOffer {
     pub fn space() -> usize {
         8 + // key
-        1 + // id
+        8 + // id
         32 + // lender
         (1 + 8) + // amount
         2 + // basis_points
@@ -140,9 +224,223 @@ impl LoanOffer {
         32 + // collection
         (1 + 4) + // ltv
         (1 + 4) + // threshold
-        1 // bump
+        (1 + 8) + // expires_at
+        (1 + 32) + // mint
+        (1 + 32) + // merkle_root
+        (4 + 32 * MAX_OFFER_COLLECTIONS) + // collections
+        (4 + 32 * MAX_COUNTERPARTIES) + // counterparties
+        (4 + (8 + 2) * MAX_RATE_POINTS) + // rate_curve
+        1 + // bump
+        1 // escrow_bump
+    }
+
+    pub fn is_expired(&self, unix_timestamp: i64) -> bool {
+        match self.expires_at {
+            Some(expires_at) => unix_timestamp > expires_at,
+            None => false,
+        }
+    }
+
+    pub fn allows_counterparty(&self, counterparty: &Pubkey) -> bool {
+        self.counterparties.is_empty() || self.counterparties.contains(counterparty)
+    }
+
+    pub fn assert_rate_curve_valid(rate_curve: &Vec<RatePoint>) -> Result<()> {
+        if rate_curve.is_empty() {
+            return Ok(());
+        }
+
+        require_gte!(rate_curve.len(), 2, ErrorCodes::InvalidRateCurve);
+        require_gte!(MAX_RATE_POINTS, rate_curve.len(), ErrorCodes::InvalidRateCurve);
+        require_gt!(rate_curve[0].duration, 0, ErrorCodes::InvalidRateCurve);
+
+        for points in rate_curve.windows(2) {
+            require_gt!(points[1].duration, points[0].duration, ErrorCodes::InvalidRateCurve);
+        }
+
+        Ok(())
+    }
+
+    // Checked whenever an offer's terms are set, as every point on the curve can be taken
+    pub fn assert_terms_valid(
+        config: &Config,
+        amount: u64,
+        basis_points: u16,
+        duration: i64,
+        rate_curve: &Vec<RatePoint>,
+    ) -> Result<()> {
+        require_gt!(duration, 0, ErrorCodes::InvalidDuration);
+        config.assert_loan_terms_valid(amount, basis_points, duration)?;
+
+        for point in rate_curve.iter() {
+            config.assert_loan_terms_valid(amount, point.basis_points, point.duration)?;
+        }
+
+        Ok(())
+    }
+
+    // Rates between two points on the curve are interpolated linearly
+    pub fn terms(&self, duration: Option<i64>) -> Result<(i64, u16)> {
+        if self.rate_curve.is_empty() {
+            if let Some(duration) = duration {
+                require_eq!(duration, self.duration, ErrorCodes::InvalidDuration);
+            }
+
+            return Ok((self.duration, self.basis_points));
+        }
+
+        let duration = duration.ok_or(ErrorCodes::InvalidDuration)?;
+        let min_duration = self.rate_curve.first().unwrap().duration;
+        let max_duration = self.rate_curve.last().unwrap().duration;
+
+        require_gte!(duration, min_duration, ErrorCodes::InvalidDuration);
+        require_gte!(max_duration, duration, ErrorCodes::InvalidDuration);
+
+        for points in self.rate_curve.windows(2) {
+            let (start, end) = (points[0], points[1]);
+
+            if duration > end.duration {
+                continue;
+            }
+
+            let basis_points = (start.basis_points as i128)
+                .checked_add(
+                    (end.basis_points as i128 - start.basis_points as i128)
+                        .checked_mul((duration - start.duration) as i128)
+                        .ok_or(ErrorCodes::NumericalOverflow)?
+                        .checked_div((end.duration - start.duration) as i128)
+                        .ok_or(ErrorCodes::NumericalOverflow)?
+                )
+                .ok_or(ErrorCodes::NumericalOverflow)?;
+
+            // Always lies between the two points' rates
+            return Ok((duration, basis_points as u16));
+        }
+
+        err!(ErrorCodes::InvalidDuration)
     }
 
     pub const PREFIX: &'static [u8] = b"loan_offer";
     pub const VAULT_PREFIX: &'static [u8] = b"loan_offer_vault";
+}
+
+/// Layout of loans created before they were sequenced, keyed by mint and borrower only
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyLoan {
+    pub state: LoanState,
+    pub borrower: Pubkey,
+    pub lender: Option<Pubkey>,
+    pub amount: Option<u64>,
+    pub basis_points: u16,
+    pub creator_basis_points: u16,
+    pub outstanding: u64,
+    pub threshold: Option<u32>,
+    pub notice_issued: Option<i64>,
+    pub duration: i64,
+    pub start_date: Option<i64>,
+    pub mint: Pubkey,
+    pub token_mint: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl LegacyLoan {
+    pub fn space() -> usize {
+        8 + // key
+        1 + // state
+        32 + // borrower
+        (1 + 32) + // lender
+        (1 + 8) + // amount
+        2 + // basis_points
+        2 + // creator_basis_points
+        8 + // outstanding
+        (1 + 4) + // threshold
+        1 + // installments
+        1 + // current_installment
+        (1 + 8) + // notice_issued
+        8 + // duration
+        (1 + 8) + // start_date
+        32 + // mint
+        (1 + 32) + // token_mint
+        1 // bump
+    }
+
+    // Legacy loans are the first position on their mint and were charged royalties as before
+    pub fn migrate(&self, collection: Pubkey, bump: u8) -> Loan {
+        Loan {
+            state: self.state,
+            borrower: self.borrower,
+            lender: self.lender,
+            amount: self.amount,
+            basis_points: self.basis_points,
+            creator_basis_points: self.creator_basis_points,
+            verified_creators_only: false,
+            payout: None,
+            permissionless: false,
+            outstanding: self.outstanding,
+            threshold: self.threshold,
+            notice_issued: self.notice_issued,
+            duration: self.duration,
+            start_date: self.start_date,
+            mint: self.mint,
+            token_mint: self.token_mint,
+            listing_expires_at: None,
+            counterparties: vec![],
+            collection,
+            exposure_recorded: false,
+            auction_ends_at: None,
+            sequence: 0,
+            bump,
+        }
+    }
+}
+
+/// Layout of offers created before ids were counted per lender
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyLoanOffer {
+    pub id: u8,
+    pub lender: Pubkey,
+    pub amount: Option<u64>,
+    pub basis_points: u16,
+    pub duration: i64,
+    pub collection: Pubkey,
+    pub ltv: Option<u32>,
+    pub threshold: Option<u32>,
+    pub bump: u8,
+    pub escrow_bump: u8,
+}
+
+impl LegacyLoanOffer {
+    pub fn space() -> usize {
+        8 + // key
+        1 + // id
+        32 + // lender
+        (1 + 8) + // amount
+        2 + // basis_points
+        8 + // duration
+        32 + // collection
+        (1 + 4) + // ltv
+        (1 + 4) + // threshold
+        1 // bump
+    }
+
+    pub fn migrate(&self, id: u64, bump: u8, escrow_bump: u8) -> LoanOffer {
+        LoanOffer {
+            id,
+            lender: self.lender,
+            amount: self.amount,
+            basis_points: self.basis_points,
+            duration: self.duration,
+            collection: self.collection,
+            ltv: self.ltv,
+            threshold: self.threshold,
+            expires_at: None,
+            mint: None,
+            merkle_root: None,
+            collections: vec![],
+            counterparties: vec![],
+            rate_curve: vec![],
+            bump,
+            escrow_bump,
+        }
+    }
 }
\ No newline at end of file
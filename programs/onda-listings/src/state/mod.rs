@@ -1,11 +1,25 @@
+pub mod attestation;
 pub mod call_option;
 pub mod collection;
+pub mod denylist;
+pub mod global_config;
+pub mod lender_risk;
 pub mod loan;
+pub mod offer_counter;
+pub mod referrer_stats;
 pub mod rental;
 pub mod token_manager;
+pub mod treasury;
 
+pub use attestation::*;
 pub use call_option::*;
 pub use collection::*;
+pub use denylist::*;
+pub use global_config::*;
+pub use lender_risk::*;
 pub use loan::*;
+pub use offer_counter::*;
+pub use referrer_stats::*;
 pub use rental::*;
 pub use token_manager::*;
+pub use treasury::*;
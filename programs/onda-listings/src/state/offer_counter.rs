@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct OfferCounter {
+    /// The wallet making offers or bids
+    pub owner: Pubkey,
+    /// The id given to the wallet's next offer or bid
+    pub next_id: u64,
+    /// misc
+    pub bump: u8,
+}
+
+impl OfferCounter {
+    pub fn space() -> usize {
+        8 + // key
+        32 + // owner
+        8 + // next_id
+        1 // bump
+    }
+
+    pub const PREFIX: &'static [u8] = b"offer_counter";
+}
\ No newline at end of file
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::error::*;
+
+#[account]
+pub struct ReferrerStats {
+    /// The wallet receiving referral fees
+    pub referrer: Pubkey,
+    /// Number of loans and options originated through the referrer
+    pub referrals: u64,
+    /// Total lamports earned by the referrer, held on this account until claimed
+    pub total_fees: u64,
+    /// misc
+    pub bump: u8,
+}
+
+impl ReferrerStats {
+    pub fn space() -> usize {
+        8 + // key
+        32 + // referrer
+        8 + // referrals
+        8 + // total_fees
+        1 // bump
+    }
+
+    pub fn record_referral(&mut self, fee: u64) -> Result<()> {
+        self.referrals = self.referrals.checked_add(1).ok_or(ErrorCodes::NumericalOverflow)?;
+        self.total_fees = self.total_fees.checked_add(fee).ok_or(ErrorCodes::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub const PREFIX: &'static [u8] = b"referrer_stats";
+}
\ No newline at end of file
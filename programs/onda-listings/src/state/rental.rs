@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::state::Payout;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
 pub enum RentalState {
@@ -14,6 +15,10 @@ pub struct Rental {
     pub amount: u64,
     /// The creator fee
     pub creator_basis_points: u16,
+    /// Whether royalties are only paid to verified creators
+    pub verified_creators_only: bool,
+    /// (Optional) The collection authority's share of the creator fee
+    pub payout: Option<Payout>,
     /// The NFT lender
     pub lender: Pubkey,
     /// The NFT borrower
@@ -39,6 +44,8 @@ impl Rental {
         1 + // state
         8 + // amount
         2 + // creator_basis_points
+        1 + // verified_creators_only
+        (1 + 32 + 2) + // payout
         32 + // lender
         (1 + 32) + // borrower
         8 + // expiry
@@ -11,6 +11,8 @@ pub struct AccountState {
 pub struct TokenManager {
     pub authority: Option<Pubkey>,
     pub accounts: AccountState,
+    /// Sequence number of the latest loan or call option on the mint, zero if there has been none
+    pub sequence: u64,
     /// Misc
     pub bump: u8,
 }
@@ -23,6 +25,45 @@ impl TokenManager {
       8 + // key
       (1 + 32) + // authority
       (1 * 3) + // account state
+      8 + // sequence
       1 // bump
   }
+
+    pub fn next_sequence(&self) -> u64 {
+        self.sequence + 1
+    }
+
+    /// Clears the authority once the token is released. The account is kept so that
+    /// the sequence carries on and earlier loans and call options remain addressable.
+    pub fn release(&mut self) {
+        self.authority = None;
+        self.accounts.loan = false;
+        self.accounts.call_option = false;
+    }
+}
+
+/// Layout of token managers created before loans and call options were sequenced
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyTokenManager {
+    pub authority: Option<Pubkey>,
+    pub accounts: AccountState,
+    pub bump: u8,
+}
+
+impl LegacyTokenManager {
+    pub fn space() -> usize {
+      8 + // key
+      (1 + 32) + // authority
+      (1 * 3) + // account state
+      1 // bump
+  }
+
+    pub fn migrate(&self) -> TokenManager {
+        TokenManager {
+            authority: self.authority,
+            accounts: self.accounts.clone(),
+            sequence: 0,
+            bump: self.bump,
+        }
+    }
 }
\ No newline at end of file
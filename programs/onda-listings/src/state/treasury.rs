@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+/// Holds the protocol fees, which are set on the global config
+#[account]
+pub struct Treasury {
+    /// misc
+    pub bump: u8,
+}
+
+impl Treasury {
+    pub fn space() -> usize {
+        8 + // key
+        1 // bump
+    }
+
+    pub const PREFIX: &'static [u8] = b"treasury";
+}
\ No newline at end of file
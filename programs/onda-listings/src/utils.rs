@@ -1,10 +1,13 @@
 use {
-    std::{slice::Iter},
+    std::{convert::TryInto, slice::Iter},
     anchor_lang::{
         prelude::*,
         solana_program::{
+            ed25519_program,
+            keccak,
             program::{invoke, invoke_signed},
-            system_instruction::{transfer}
+            system_instruction::{allocate, assign, transfer},
+            sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
         },
     },
     anchor_spl::token::{TokenAccount},
@@ -15,7 +18,7 @@ use {
 };
 
 use crate::constants::*;
-use crate::state::{Rental, Collection, TokenManager};
+use crate::state::{Attestation, AttestationReceipt, Rental, Collection, CollectionType, Config, Denylist, GlobalConfig, Loan, LenderRisk, Payout, ReferrerStats, TokenManager, Treasury};
 use crate::error::*;
 
 pub fn handle_delegate_and_freeze<'info>(
@@ -35,7 +38,6 @@ pub fn handle_delegate_and_freeze<'info>(
     let token_manager_key = token_manager.key();
     let owner_key = owner.key();
     let token_account_key = token_account.key();
-    let mint_key = mint.key();
     let metadata_key = metadata_info.key();
     let edition_key = edition.key();
     let system_program_key = system_program.key(); 
@@ -106,6 +108,50 @@ pub fn handle_delegate_and_freeze<'info>(
         &delegate_accounts[..],
     )?;
 
+    handle_freeze(
+        token_manager,
+        owner.clone(),
+        owner,
+        token_account,
+        token_record,
+        mint,
+        metadata_info,
+        edition,
+        token_program,
+        system_program,
+        sysvar_instructions,
+        authorization_rules_program,
+        authorization_rules,
+    )
+}
+
+pub fn handle_freeze<'info>(
+    token_manager: &mut Account<'info, TokenManager>,
+    owner: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    token_account: AccountInfo<'info>,
+    token_record: Option<AccountInfo<'info>>,
+    mint: AccountInfo<'info>,
+    metadata_info: AccountInfo<'info>,
+    edition: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    sysvar_instructions: AccountInfo<'info>,
+    authorization_rules_program: AccountInfo<'info>,
+    authorization_rules: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let token_manager_key = token_manager.key();
+    let owner_key = owner.key();
+    let payer_key = payer.key();
+    let token_account_key = token_account.key();
+    let mint_key = mint.key();
+    let metadata_key = metadata_info.key();
+    let edition_key = edition.key();
+    let system_program_key = system_program.key(); 
+    let sysvar_instructions_key = sysvar_instructions.key();
+    let token_program_key = token_program.key();
+    let authorization_rules_program_key = authorization_rules_program.key();
+
     let mut lock_builder = builders::LockBuilder::new();
 
     lock_builder
@@ -115,7 +161,7 @@ pub fn handle_delegate_and_freeze<'info>(
         .mint(mint_key)
         .metadata(metadata_key)
         .edition(edition_key)
-        .payer(owner_key)
+        .payer(payer_key)
         .system_program(system_program_key)
         .sysvar_instructions(sysvar_instructions_key)
         .spl_token_program(token_program_key);
@@ -130,6 +176,7 @@ pub fn handle_delegate_and_freeze<'info>(
     let mut lock_accounts = vec![
         token_manager.to_account_info(),
         owner.to_account_info(),
+        payer.to_account_info(),
         token_account.to_account_info(),
         mint.to_account_info(),
         metadata_info.to_account_info(),
@@ -168,9 +215,10 @@ pub fn handle_delegate_and_freeze<'info>(
     Ok(())
 }
 
-pub fn handle_thaw_and_revoke<'info>(
+pub fn handle_thaw<'info>(
     token_manager: &mut Account<'info, TokenManager>,
     owner: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
     token_account: AccountInfo<'info>,
     token_record: Option<AccountInfo<'info>>,
     mint: AccountInfo<'info>,
@@ -184,6 +232,7 @@ pub fn handle_thaw_and_revoke<'info>(
 ) -> Result<()> {
     let token_manager_key = token_manager.key();
     let owner_key = owner.key();
+    let payer_key = payer.key();
     let token_account_key = token_account.key();
     let mint_key = mint.key();
     let metadata_key = metadata_info.key();
@@ -201,7 +250,7 @@ pub fn handle_thaw_and_revoke<'info>(
         .mint(mint_key)
         .metadata(metadata_key)
         .edition(edition_key)
-        .payer(owner_key)
+        .payer(payer_key)
         .system_program(system_program_key)
         .sysvar_instructions(sysvar_instructions_key)
         .spl_token_program(token_program_key);
@@ -210,6 +259,7 @@ pub fn handle_thaw_and_revoke<'info>(
     let mut unlock_accounts = vec![
         token_manager.to_account_info(),
         owner.to_account_info(),
+        payer.to_account_info(),
         token_account.to_account_info(),
         mint.to_account_info(),
         metadata_info.to_account_info(),
@@ -252,6 +302,81 @@ pub fn handle_thaw_and_revoke<'info>(
         signer_seeds
     )?;
 
+    Ok(())
+}
+
+pub fn handle_thaw_and_revoke<'info>(
+    token_manager: &mut Account<'info, TokenManager>,
+    owner: AccountInfo<'info>,
+    token_account: AccountInfo<'info>,
+    token_record: Option<AccountInfo<'info>>,
+    mint: AccountInfo<'info>,
+    metadata_info: AccountInfo<'info>,
+    edition: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    sysvar_instructions: AccountInfo<'info>,
+    authorization_rules_program: AccountInfo<'info>,
+    authorization_rules: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    handle_thaw(
+        token_manager,
+        owner.clone(),
+        owner.clone(),
+        token_account.clone(),
+        token_record.clone(),
+        mint.clone(),
+        metadata_info.clone(),
+        edition.clone(),
+        token_program.clone(),
+        system_program.clone(),
+        sysvar_instructions.clone(),
+        authorization_rules_program.clone(),
+        authorization_rules.clone(),
+    )?;
+
+    handle_revoke(
+        token_manager,
+        owner,
+        token_account,
+        token_record,
+        mint,
+        metadata_info,
+        edition,
+        token_program,
+        system_program,
+        sysvar_instructions,
+        authorization_rules_program,
+        authorization_rules,
+    )
+}
+
+// The token owner must sign, as only they can revoke a delegate
+pub fn handle_revoke<'info>(
+    token_manager: &mut Account<'info, TokenManager>,
+    owner: AccountInfo<'info>,
+    token_account: AccountInfo<'info>,
+    token_record: Option<AccountInfo<'info>>,
+    mint: AccountInfo<'info>,
+    metadata_info: AccountInfo<'info>,
+    edition: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    sysvar_instructions: AccountInfo<'info>,
+    authorization_rules_program: AccountInfo<'info>,
+    authorization_rules: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let token_manager_key = token_manager.key();
+    let owner_key = owner.key();
+    let token_account_key = token_account.key();
+    let mint_key = mint.key();
+    let metadata_key = metadata_info.key();
+    let edition_key = edition.key();
+    let system_program_key = system_program.key(); 
+    let sysvar_instructions_key = sysvar_instructions.key();
+    let token_program_key = token_program.key();
+    let authorization_rules_program_key = authorization_rules_program.key();
+
     let mut revoke_builder = builders::RevokeBuilder::new();
 
     revoke_builder.delegate(token_manager_key)
@@ -629,6 +754,8 @@ pub fn withdraw_from_rental_escrow<'info>(
     let remaining_amount = pay_creator_fees_with_signer(
         amount,
         rental.creator_basis_points,
+        rental.verified_creators_only,
+        rental.payout,
         mint,
         metadata_info,
         rental_escrow,
@@ -766,8 +893,8 @@ pub fn assert_metadata_valid<'a>(
 pub fn assert_collection_valid<'a>(
     metadata: &AccountInfo<'a>,
     mint: Pubkey,
-    collection_pda: Pubkey,
-    program_id: Pubkey,
+    collection: &Collection,
+    proof: &Vec<[u8; 32]>,
 ) -> Result<()> {
     let metadata = Metadata::deserialize(
         &mut metadata.data.borrow_mut().as_ref()
@@ -775,28 +902,57 @@ pub fn assert_collection_valid<'a>(
 
     require_keys_eq!(metadata.mint, mint.key(), ErrorCodes::InvalidMint);
 
-    match metadata.collection {
-        Some(collection) => {
-            let seeds = &[
-                Collection::PREFIX,
-                collection.key.as_ref(),
-            ];
-            let (address, _) = Pubkey::find_program_address(
-                seeds, 
-                &program_id
-            );
-
-            require_keys_eq!(address, collection_pda, ErrorCodes::InvalidCollection);
-            require!(collection.verified, ErrorCodes::InvalidCollection);
+    match collection.collection_type {
+        CollectionType::Certified => {
+            match metadata.collection {
+                Some(metadata_collection) => {
+                    require_keys_eq!(metadata_collection.key, collection.mint, ErrorCodes::InvalidCollection);
+                    require!(metadata_collection.verified, ErrorCodes::InvalidCollection);
+                }
+                None => {
+                    return err!(ErrorCodes::InvalidCollection);
+                }
+            }
+        }
+        CollectionType::Creator => {
+            let creator = metadata.data.creators
+                .unwrap_or_default()
+                .into_iter()
+                .find(|creator| creator.verified)
+                .ok_or(ErrorCodes::InvalidCollection)?;
+
+            require_keys_eq!(creator.address, collection.mint, ErrorCodes::InvalidCollection);
         }
-        None => {
-            return err!(ErrorCodes::InvalidCollection);
+        CollectionType::MerkleRoot => {
+            assert_merkle_proof_valid(proof, collection.mint.to_bytes(), mint)?;
         }
     }
 
     Ok(())
 }
   
+// Leaves are the keccak hash of the mint address and each pair of nodes is
+// hashed in sorted order, so the proof does not need to encode left/right
+pub fn assert_merkle_proof_valid(
+    proof: &Vec<[u8; 32]>,
+    root: [u8; 32],
+    mint: Pubkey,
+) -> Result<()> {
+    let mut computed_hash = keccak::hashv(&[mint.as_ref()]).0;
+
+    for node in proof.iter() {
+        computed_hash = if computed_hash <= *node {
+            keccak::hashv(&[&computed_hash, node]).0
+        } else {
+            keccak::hashv(&[node, &computed_hash]).0
+        };
+    }
+
+    require!(computed_hash == root, ErrorCodes::InvalidProof);
+
+    Ok(())
+}
+  
 pub fn calculate_fee_from_basis_points(
     amount: u128,
     basis_points: u128,
@@ -816,9 +972,44 @@ pub struct CreatorFee<'a> {
     pub account_info: AccountInfo<'a>
 }
 
+pub fn get_creator_basis_points<'a>(
+    config: &Config,
+    basis_points: u16,
+    metadata_info: &AccountInfo<'a>,
+) -> Result<u16> {
+    let metadata = Metadata::deserialize(
+        &mut metadata_info.data.borrow_mut().as_ref()
+    )?;
+
+    Ok(config.creator_basis_points(basis_points, metadata.data.seller_fee_basis_points))
+}
+
+pub fn get_exercise_basis_points<'a>(
+    config: &Config,
+    metadata_info: &AccountInfo<'a>,
+) -> Result<u16> {
+    let metadata = Metadata::deserialize(
+        &mut metadata_info.data.borrow_mut().as_ref()
+    )?;
+
+    Ok(config.exercise_basis_points(metadata.data.seller_fee_basis_points))
+}
+
+pub fn get_seller_fee_basis_points<'a>(
+    metadata_info: &AccountInfo<'a>,
+) -> Result<u16> {
+    let metadata = Metadata::deserialize(
+        &mut metadata_info.data.borrow_mut().as_ref()
+    )?;
+
+    Ok(metadata.data.seller_fee_basis_points)
+}
+
 pub fn get_creator_fees<'a>(
     amount: u64,
     basis_points: u16,
+    verified_creators_only: bool,
+    payout: Option<Payout>,
     mint: &AccountInfo<'a>,
     metadata_info: &AccountInfo<'a>,
     remaining_accounts: &mut Iter<AccountInfo<'a>>,
@@ -836,52 +1027,97 @@ pub fn get_creator_fees<'a>(
         &mint
     )?;
 
+    let creators: Vec<_> = metadata.data.creators
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|creator| creator.verified || !verified_creators_only)
+        .collect();
+    let total_shares: u128 = creators.iter().map(|creator| creator.share as u128).sum();
+
     let total_fee = calculate_fee_from_basis_points(amount as u128, basis_points as u128)?;
-    let mut remaining_fee = total_fee;
+    let payout_fee = match payout {
+        Some(payout) => calculate_fee_from_basis_points(
+            total_fee as u128,
+            payout.share_basis_points as u128
+        )?,
+        None => 0,
+    };
+    let mut creator_fee = total_fee
+            .checked_sub(payout_fee)
+            .ok_or(ErrorCodes::NumericalOverflow)?;
+
+    if total_shares == 0 {
+        msg!("No creators found in metadata");
+        creator_fee = 0;
+    }
+
+    let mut remaining_fee = creator_fee;
     let remaining_amount = amount
-            .checked_sub(total_fee)
+            .checked_sub(creator_fee)
+            .ok_or(ErrorCodes::NumericalOverflow)?
+            .checked_sub(payout_fee)
             .ok_or(ErrorCodes::NumericalOverflow)?;
     
     let mut fees: Vec<CreatorFee> = Vec::new();
 
-    msg!("Paying {} lamports in royalties", total_fee);
-        
-    match metadata.data.creators {
-        Some(creators) => {
-            for creator in creators {
-                let pct = creator.share as u128;
-                let amount = pct.checked_mul(total_fee as u128)
-                        .ok_or(ErrorCodes::NumericalOverflow)?
-                        .checked_div(100)
-                        .ok_or(ErrorCodes::NumericalOverflow)? as u64;
-                remaining_fee = remaining_fee
-                        .checked_sub(amount)
-                        .ok_or(ErrorCodes::NumericalOverflow)?;
-
-                let current_creator_info = next_account_info(remaining_accounts)?;
-                let address = current_creator_info.key();
-                require_keys_eq!(address, creator.address);
-
-                fees.push(CreatorFee {
-                    amount,
-                    address,
-                    account_info: current_creator_info.to_account_info()
-                });
-            }
-        }
-        None => {
-            msg!("No creators found in metadata");
+    msg!("Paying {} lamports in royalties", creator_fee);
+
+    // Shares are split between the eligible creators only
+    for creator in creators {
+        // Every eligible creator is passed, so the accounts are read even when nothing is owed
+        let current_creator_info = next_account_info(remaining_accounts)?;
+        let address = current_creator_info.key();
+        require_keys_eq!(address, creator.address);
+
+        if creator_fee == 0 {
+            continue;
         }
+
+        let pct = creator.share as u128;
+        let amount = pct.checked_mul(creator_fee as u128)
+                .ok_or(ErrorCodes::NumericalOverflow)?
+                .checked_div(total_shares)
+                .ok_or(ErrorCodes::NumericalOverflow)? as u64;
+        remaining_fee = remaining_fee
+                .checked_sub(amount)
+                .ok_or(ErrorCodes::NumericalOverflow)?;
+
+        fees.push(CreatorFee {
+            amount,
+            address,
+            account_info: current_creator_info.to_account_info()
+        });
+    }
+
+    // Rounding dust always goes to the first eligible creator
+    if let Some(creator_fee) = fees.first_mut() {
+        creator_fee.amount = creator_fee.amount
+            .checked_add(remaining_fee)
+            .ok_or(ErrorCodes::NumericalOverflow)?;
     }
 
-    let remaining = remaining_amount.checked_add(remaining_fee).ok_or(ErrorCodes::NumericalOverflow)?;
+    // The payout account follows the creators in the remaining accounts
+    if let Some(payout) = payout {
+        msg!("Paying {} lamports to the collection authority", payout_fee);
 
-    Ok((fees, remaining))
+        let payout_info = next_account_info(remaining_accounts)?;
+        require_keys_eq!(payout_info.key(), payout.address);
+
+        fees.push(CreatorFee {
+            amount: payout_fee,
+            address: payout.address,
+            account_info: payout_info.to_account_info()
+        });
+    }
+
+    Ok((fees, remaining_amount))
 }
 
 pub fn pay_creator_fees<'a>(
     amount: u64,
     basis_points: u16,
+    verified_creators_only: bool,
+    payout: Option<Payout>,
     mint: &AccountInfo<'a>,
     metadata_info: &AccountInfo<'a>,
     fee_payer: &mut AccountInfo<'a>,
@@ -890,6 +1126,8 @@ pub fn pay_creator_fees<'a>(
     let (fees, remaining_amount) = get_creator_fees(
         amount,
         basis_points,
+        verified_creators_only,
+        payout,
         mint,
         metadata_info,
         remaining_accounts,
@@ -915,6 +1153,8 @@ pub fn pay_creator_fees<'a>(
 pub fn pay_creator_fees_with_signer<'a>(
     amount: u64,
     basis_points: u16,
+    verified_creators_only: bool,
+    payout: Option<Payout>,
     mint: &AccountInfo<'a>,
     metadata_info: &AccountInfo<'a>,
     fee_payer: &mut AccountInfo<'a>,
@@ -924,6 +1164,8 @@ pub fn pay_creator_fees_with_signer<'a>(
     let (fees, remaining_amount) = get_creator_fees(
         amount,
         basis_points,
+        verified_creators_only,
+        payout,
         mint,
         metadata_info,
         remaining_accounts,
@@ -947,102 +1189,501 @@ pub fn pay_creator_fees_with_signer<'a>(
     Ok(remaining_amount)
 }
 
-pub fn pay_creator_royalties<'a>(
+pub fn calculate_loan_repayment_fee(
     amount: u64,
-    mint: &AccountInfo<'a>,
-    metadata_info: &AccountInfo<'a>,
-    fee_payer: &mut AccountInfo<'a>,
-    remaining_accounts: &mut Iter<AccountInfo<'a>>,
+    basis_points: u16,
+    duration: i64,
+    is_overdue: bool,
 ) -> Result<u64> {
-    let metadata = Metadata::deserialize(
-        &mut metadata_info.data.borrow_mut().as_ref()
-    )?;
-    let basis_points = metadata.data.seller_fee_basis_points;
-    let (fees, remaining_amount) = get_creator_fees(
-        amount,
-        basis_points,
-        mint,
-        metadata_info,
-        remaining_accounts,
+    let annual_fee = calculate_fee_from_basis_points(amount as u128, basis_points as u128)?;
+
+    let mut interest_due = annual_fee.checked_mul(duration as u64)
+        .ok_or(ErrorCodes::NumericalOverflow)?
+        .checked_div(SECONDS_PER_YEAR as u64)
+        .ok_or(ErrorCodes::NumericalOverflow)?;
+
+    // let mut amount_due = amount.checked_add(interest_due).ok_or(ErrorCodes::NumericalOverflow)?;
+    msg!("interest_due {}", interest_due);
+
+    if is_overdue {
+        let late_repayment_fee = calculate_fee_from_basis_points(amount as u128, LATE_REPAYMENT_FEE_BASIS_POINTS)?;
+        msg!("late_repayment_fee {}", late_repayment_fee);
+        interest_due = interest_due.checked_add(late_repayment_fee).ok_or(ErrorCodes::NumericalOverflow)?;
+    }
+    
+    
+    Ok(interest_due)
+}
+
+// Lenders without a risk account have no limits to enforce
+pub fn record_lender_exposure<'info>(
+    lender_risk: &UncheckedAccount<'info>,
+    loan: &mut Account<'info, Loan>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    if lender_risk.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut lender_risk = Account::<LenderRisk>::try_from(&lender_risk.to_account_info())?;
+    lender_risk.add_exposure(loan.collection, loan.outstanding)?;
+    lender_risk.exit(program_id)?;
+
+    loan.exposure_recorded = true;
+
+    Ok(())
+}
+
+pub fn release_lender_exposure<'info>(
+    lender_risk: &UncheckedAccount<'info>,
+    loan: &Account<'info, Loan>,
+    amount: u64,
+    settled: bool,
+    program_id: &Pubkey,
+) -> Result<()> {
+    if !loan.exposure_recorded {
+        return Ok(());
+    }
+
+    let mut lender_risk = Account::<LenderRisk>::try_from(&lender_risk.to_account_info())?;
+    lender_risk.remove_exposure(loan.collection, amount, settled)?;
+    lender_risk.exit(program_id)?;
+
+    Ok(())
+}
+
+// Collections without a denylist allow every mint
+pub fn assert_mint_not_denied<'info>(
+    denylist: &UncheckedAccount<'info>,
+    collection: &Pubkey,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (address, _) = Pubkey::find_program_address(
+        &[
+            Denylist::PREFIX,
+            collection.as_ref(),
+        ],
+        program_id
+    );
+
+    require_keys_eq!(address, denylist.key(), ErrorCodes::DerivedKeyInvalid);
+
+    if denylist.data_is_empty() {
+        return Ok(());
+    }
+
+    let denylist = Account::<Denylist>::try_from(&denylist.to_account_info())?;
+    require!(!denylist.is_denied(mint), ErrorCodes::MintDenied);
+
+    Ok(())
+}
+
+// Without a co-signer, the instruction must be immediately preceded by an Ed25519 program
+// instruction verifying an attestation over it from one of the global config's signers.
+// Using an attestation creates its receipt, so it can't be replayed. Permissionless
+// collections need neither
+pub fn assert_attested<'info>(
+    global_config: &GlobalConfig,
+    signer: &Option<Signer<'info>>,
+    attestation_receipt: &Option<UncheckedAccount<'info>>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    sysvar_instructions: &UncheckedAccount<'info>,
+    permissionless: bool,
+) -> Result<()> {
+    if signer.is_some() || permissionless {
+        return Ok(());
+    }
+
+    let sysvar_instructions = sysvar_instructions.to_account_info();
+    let current_index = load_current_index_checked(&sysvar_instructions)?;
+    require_gt!(current_index, 0, ErrorCodes::AttestationNotFound);
+
+    let instruction = load_instruction_at_checked(current_index as usize, &sysvar_instructions)?;
+    let ed25519_instruction = load_instruction_at_checked(
+        (current_index - 1) as usize,
+        &sysvar_instructions
     )?;
+    require_keys_eq!(ed25519_instruction.program_id, ed25519_program::ID, ErrorCodes::AttestationNotFound);
 
-    for creator_fee in fees {
+    let (public_key, message) = get_ed25519_signed_message(&ed25519_instruction.data)?;
+    require!(global_config.is_signer(&public_key), ErrorCodes::InvalidAttestation);
+
+    let attestation = Attestation::try_from_slice(message)
+        .map_err(|_| error!(ErrorCodes::InvalidAttestation))?;
+
+    require!(
+        attestation.instruction_hash == Attestation::hash_instruction(&instruction),
+        ErrorCodes::InvalidAttestation
+    );
+    require_gt!(attestation.expiry, Clock::get()?.unix_timestamp, ErrorCodes::AttestationExpired);
+
+    let attestation_receipt = match attestation_receipt {
+        Some(attestation_receipt) => attestation_receipt,
+        None => return err!(ErrorCodes::InvalidAttestation),
+    };
+
+    create_attestation_receipt(
+        &attestation_receipt.to_account_info(),
+        &public_key,
+        &attestation,
+        payer,
+        system_program,
+    )
+}
+
+// Receipts are derived from the attesting signer and the attestation's nonce, as the
+// receipt is itself one of the attested instruction's accounts
+fn create_attestation_receipt<'info>(
+    attestation_receipt: &AccountInfo<'info>,
+    public_key: &Pubkey,
+    attestation: &Attestation,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let nonce = attestation.nonce.to_le_bytes();
+    let (address, bump) = Pubkey::find_program_address(
+        &[
+            AttestationReceipt::PREFIX,
+            public_key.as_ref(),
+            &nonce,
+        ],
+        &crate::ID,
+    );
+
+    require_keys_eq!(address, attestation_receipt.key(), ErrorCodes::DerivedKeyInvalid);
+    require!(attestation_receipt.data_is_empty(), ErrorCodes::AttestationUsed);
+
+    let signer_bump = &[bump];
+    let signer_seeds = &[&[
+        AttestationReceipt::PREFIX,
+        public_key.as_ref(),
+        &nonce,
+        signer_bump,
+    ][..]];
+
+    // Funded, allocated and assigned separately, as create_account fails if the
+    // address already holds lamports
+    let space = AttestationReceipt::space();
+    let rent = Rent::get()?.minimum_balance(space).saturating_sub(attestation_receipt.lamports());
+
+    if rent > 0 {
         invoke(
             &transfer(
-                &fee_payer.key(),
-                &creator_fee.address,
-                creator_fee.amount,
+                &payer.key(),
+                &attestation_receipt.key(),
+                rent,
             ),
             &[
-                fee_payer.to_account_info(),
-                creator_fee.account_info,
-            ],
+                payer.clone(),
+                attestation_receipt.clone(),
+                system_program.clone(),
+            ]
         )?;
     }
 
-    Ok(remaining_amount)
-}
-
-pub fn pay_creator_royalties_with_signer<'a>(
-    amount: u64,
-    mint: &AccountInfo<'a>,
-    metadata_info: &AccountInfo<'a>,
-    fee_payer: &mut AccountInfo<'a>,
-    remaining_accounts: &mut Iter<AccountInfo<'a>>,
-    signer_seeds: &[&[&[u8]]],
-) -> Result<u64> {
-    let metadata = Metadata::deserialize(
-        &mut metadata_info.data.borrow_mut().as_ref()
+    invoke_signed(
+        &allocate(&attestation_receipt.key(), space as u64),
+        &[
+            attestation_receipt.clone(),
+            system_program.clone(),
+        ],
+        signer_seeds
     )?;
-    let basis_points = metadata.data.seller_fee_basis_points;
-    let (fees, remaining_amount) = get_creator_fees(
-        amount,
-        basis_points,
-        mint,
-        metadata_info,
-        remaining_accounts,
+
+    invoke_signed(
+        &assign(&attestation_receipt.key(), &crate::ID),
+        &[
+            attestation_receipt.clone(),
+            system_program.clone(),
+        ],
+        signer_seeds
     )?;
 
-    for creator_fee in fees {
+    let receipt = AttestationReceipt {
+        payer: payer.key(),
+        expiry: attestation.expiry,
+        bump,
+    };
+    let mut data = attestation_receipt.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    receipt.try_serialize(&mut writer)?;
+
+    Ok(())
+}
+
+// The Ed25519 instruction must carry exactly one signature, with the signature, public key
+// and message all held in its own data, as built by web3.js' Ed25519Program. Its data is:
+//
+//   0      u8   number of signatures, which must be 1
+//   1      u8   padding
+//   2..16  u16  signature offset, signature instruction index, public key offset, public key
+//              instruction index, message offset, message size, message instruction index
+//
+// All three instruction indexes must be u16::MAX, meaning the current instruction, as data
+// read from other instructions isn't checked here. The message is a borsh Attestation.
+fn get_ed25519_signed_message(data: &[u8]) -> Result<(Pubkey, &[u8])> {
+    require!(data.len() >= 16 && data[0] == 1, ErrorCodes::UnsupportedAttestationLayout);
+
+    let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+
+    let signature_instruction_index = read_u16(4);
+    let public_key_offset = read_u16(6) as usize;
+    let public_key_instruction_index = read_u16(8);
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    let message_instruction_index = read_u16(14);
+
+    require!(
+        signature_instruction_index == u16::MAX &&
+        public_key_instruction_index == u16::MAX &&
+        message_instruction_index == u16::MAX,
+        ErrorCodes::UnsupportedAttestationLayout
+    );
+
+    let public_key: [u8; 32] = data
+        .get(public_key_offset..public_key_offset + 32)
+        .and_then(|public_key| public_key.try_into().ok())
+        .ok_or(ErrorCodes::UnsupportedAttestationLayout)?;
+    let message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ErrorCodes::UnsupportedAttestationLayout)?;
+
+    Ok((Pubkey::new_from_array(public_key), message))
+}
+
+// Moves the difference between the current and new amount into or out of an escrow vault
+pub fn adjust_escrow_balance<'info>(
+    owner: AccountInfo<'info>,
+    escrow_payment_account: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    current_amount: u64,
+    new_amount: u64,
+) -> Result<()> {
+    if new_amount > current_amount {
+        invoke(
+            &transfer(
+                &owner.key(),
+                &escrow_payment_account.key(),
+                new_amount - current_amount,
+            ),
+            &[
+                owner,
+                escrow_payment_account,
+            ]
+        )?;
+    } else if current_amount > new_amount {
         invoke_signed(
             &transfer(
-                &fee_payer.key(),
-                &creator_fee.address,
-                creator_fee.amount,
+                &escrow_payment_account.key(),
+                &owner.key(),
+                current_amount - new_amount,
             ),
             &[
-                fee_payer.to_account_info(),
-                creator_fee.account_info,
+                escrow_payment_account,
+                owner,
             ],
-            signer_seeds,
+            signer_seeds
         )?;
     }
 
-    Ok(remaining_amount)
+    Ok(())
 }
 
-pub fn calculate_loan_repayment_fee(
+// Legacy accounts are unchecked, so they are closed by hand once migrated to their new address
+pub fn close_legacy_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let destination_lamports = destination.lamports()
+        .checked_add(account.lamports())
+        .ok_or(ErrorCodes::NumericalOverflow)?;
+
+    **destination.try_borrow_mut_lamports()? = destination_lamports;
+    **account.try_borrow_mut_lamports()? = 0;
+
+    account.assign(&anchor_lang::system_program::ID);
+    account.realloc(0, false)?;
+
+    Ok(())
+}
+
+// Batch instructions take each offer or bid as [account, escrow_payment_account, collection]
+// remaining accounts, so they're checked here against the seeds they were created with
+pub fn assert_batch_accounts_valid<'info>(
+    accounts: &[AccountInfo<'info>],
+    collection: &Account<'info, Collection>,
+    signer: &Option<Signer<'info>>,
+    seeds: &[&[u8]],
+    vault_prefix: &[u8],
+    escrow_bump: u8,
+    program_id: &Pubkey,
+) -> Result<()> {
+    require!(accounts[0].is_writable, ErrorCodes::InvalidState);
+    require!(accounts[1].is_writable, ErrorCodes::InvalidState);
+    require!(signer.is_some() || collection.config.permissionless, ErrorCodes::Unauthorized);
+
+    let address = Pubkey::create_program_address(seeds, program_id)
+        .map_err(|_| ErrorCodes::DerivedKeyInvalid)?;
+
+    require_keys_eq!(address, accounts[0].key(), ErrorCodes::DerivedKeyInvalid);
+
+    let escrow_address = Pubkey::create_program_address(
+        &[
+            vault_prefix,
+            accounts[0].key.as_ref(),
+            &[escrow_bump],
+        ],
+        program_id,
+    ).map_err(|_| ErrorCodes::DerivedKeyInvalid)?;
+
+    require_keys_eq!(escrow_address, accounts[1].key(), ErrorCodes::DerivedKeyInvalid);
+
+    Ok(())
+}
+
+// Pays anything held above the rent-exempt minimum of a program account, which owners may
+// top up to reward whoever reclaims it, to the caller. The rent itself is left for the close.
+pub fn pay_reclaim_tip<'info>(
+    account: &AccountInfo<'info>,
+    caller: &AccountInfo<'info>,
+) -> Result<()> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(account.data_len());
+    let tip = account.lamports().saturating_sub(rent_exempt_minimum);
+
+    if tip > 0 {
+        msg!("Paying {} lamports reclaim tip", tip);
+
+        **account.try_borrow_mut_lamports()? -= tip;
+        **caller.try_borrow_mut_lamports()? += tip;
+    }
+
+    Ok(())
+}
+
+// Protocol fees are only charged once the treasury has been initialized
+fn load_treasury<'info>(treasury: &UncheckedAccount<'info>) -> Result<Option<Treasury>> {
+    if treasury.data_is_empty() {
+        return Ok(None);
+    }
+
+    let treasury = Account::<Treasury>::try_from(&treasury.to_account_info())?;
+
+    Ok(Some(treasury.into_inner()))
+}
+
+pub fn get_protocol_fee<'info>(
+    treasury: &UncheckedAccount<'info>,
+    global_config: &GlobalConfig,
+    config: &Config,
     amount: u64,
-    basis_points: u16,
-    duration: i64,
-    is_overdue: bool,
 ) -> Result<u64> {
-    let annual_fee = calculate_fee_from_basis_points(amount as u128, basis_points as u128)?;
+    match load_treasury(treasury)? {
+        Some(_) => calculate_fee_from_basis_points(
+            amount as u128,
+            global_config.fee_basis_points(config) as u128
+        ),
+        None => Ok(0),
+    }
+}
 
-    let mut interest_due = annual_fee.checked_mul(duration as u64)
-        .ok_or(ErrorCodes::NumericalOverflow)?
-        .checked_div(SECONDS_PER_YEAR as u64)
-        .ok_or(ErrorCodes::NumericalOverflow)?;
+pub fn get_origination_fee<'info>(
+    treasury: &UncheckedAccount<'info>,
+    global_config: &GlobalConfig,
+    amount: u64,
+) -> Result<u64> {
+    match load_treasury(treasury)? {
+        Some(_) => calculate_fee_from_basis_points(
+            amount as u128,
+            global_config.fees.origination_fee_basis_points as u128
+        ),
+        None => Ok(0),
+    }
+}
 
-    // let mut amount_due = amount.checked_add(interest_due).ok_or(ErrorCodes::NumericalOverflow)?;
-    msg!("interest_due {}", interest_due);
+pub fn pay_protocol_fee<'info>(
+    fee: u64,
+    fee_payer: AccountInfo<'info>,
+    treasury: AccountInfo<'info>,
+) -> Result<()> {
+    if fee == 0 {
+        return Ok(());
+    }
 
-    if is_overdue {
-        let late_repayment_fee = calculate_fee_from_basis_points(amount as u128, LATE_REPAYMENT_FEE_BASIS_POINTS)?;
-        msg!("late_repayment_fee {}", late_repayment_fee);
-        interest_due = interest_due.checked_add(late_repayment_fee).ok_or(ErrorCodes::NumericalOverflow)?;
+    msg!("Paying {} lamports in protocol fees", fee);
+
+    invoke(
+        &transfer(
+            &fee_payer.key(),
+            &treasury.key(),
+            fee,
+        ),
+        &[
+            fee_payer,
+            treasury,
+        ]
+    )?;
+
+    Ok(())
+}
+
+pub fn pay_protocol_fee_with_signer<'info>(
+    fee: u64,
+    fee_payer: AccountInfo<'info>,
+    treasury: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if fee == 0 {
+        return Ok(());
     }
-    
-    
-    Ok(interest_due)
+
+    msg!("Paying {} lamports in protocol fees", fee);
+
+    invoke_signed(
+        &transfer(
+            &fee_payer.key(),
+            &treasury.key(),
+            fee,
+        ),
+        &[
+            fee_payer,
+            treasury,
+        ],
+        signer_seeds
+    )?;
+
+    Ok(())
+}
+
+// Referrers are paid their share out of the protocol fee into their stats account, where it
+// accrues until claimed. Paying the referrer directly fails for amounts below the rent-exempt
+// minimum while their wallet is unfunded, which would fail the whole instruction.
+pub fn get_referral_fee<'info>(
+    treasury: &UncheckedAccount<'info>,
+    global_config: &GlobalConfig,
+    protocol_fee: u64,
+    referrer: &Option<UncheckedAccount<'info>>,
+    referrer_stats: &mut Option<Box<Account<'info, ReferrerStats>>>,
+) -> Result<u64> {
+    let referrer_stats = match (referrer, referrer_stats) {
+        (Some(referrer), Some(referrer_stats)) => {
+            require_keys_eq!(referrer.key(), referrer_stats.referrer, ErrorCodes::InvalidReferrer);
+            referrer_stats
+        },
+        (None, None) => return Ok(0),
+        _ => return err!(ErrorCodes::InvalidReferrer),
+    };
+
+    let referral_fee = match load_treasury(treasury)? {
+        Some(_) => calculate_fee_from_basis_points(
+            protocol_fee as u128,
+            global_config.fees.referral_share_basis_points as u128
+        )?,
+        None => 0,
+    };
+
+    referrer_stats.record_referral(referral_fee)?;
+
+    Ok(referral_fee)
 }
\ No newline at end of file
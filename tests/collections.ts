@@ -34,6 +34,7 @@ describe("Collections", async () => {
       collectonData.mint.toBase58(),
       collection.mint.address.toBase58()
     );
+    assert.deepEqual(collectonData.collectionType, { certified: {} });
     assert.deepEqual(collectonData.config, {
       loanEnabled: true,
       loanBasisPoints: 200,
@@ -41,6 +42,16 @@ describe("Collections", async () => {
       optionBasisPoints: 200,
       rentalEnabled: true,
       rentalBasisPoints: 200,
+      protocolFeeBasisPoints: null,
+      royaltyPolicy: { fixed: {} },
+      verifiedCreatorsOnly: false,
+      payout: null,
+      minLoanAmount: null,
+      maxLoanAmount: null,
+      maxLoanDuration: null,
+      maxLoanBasisPoints: null,
+      maxOptionExpiry: null,
+      permissionless: false,
     });
   });
 
@@ -54,20 +65,128 @@ describe("Collections", async () => {
       optionBasisPoints: 100,
       rentalEnabled: false,
       rentalBasisPoints: 0,
+      protocolFeeBasisPoints: 50,
+      royaltyPolicy: { full: {} },
+      verifiedCreatorsOnly: true,
+      payout: {
+        address: authority.publicKey,
+        shareBasisPoints: 2_500,
+      },
+      minLoanAmount: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10),
+      maxLoanAmount: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL * 100),
+      maxLoanDuration: new anchor.BN(86_400 * 365),
+      maxLoanBasisPoints: 10_000,
+      maxOptionExpiry: new anchor.BN(86_400 * 90),
+      permissionless: false,
     };
     await program.methods
       .updateCollection(config)
       .accounts({
         signer: signer.publicKey,
+        globalConfig: helpers.findGlobalConfigAddress(),
         authority: authority.publicKey,
         collection: collectionPda,
-        mint: collectionMint.address,
       })
       .signers([signer])
       .rpc();
 
     const collectonData = await program.account.collection.fetch(collectionPda);
-    assert.deepEqual(collectonData.config, config);
+    // BNs are compared by value
+    assert.equal(JSON.stringify(collectonData.config), JSON.stringify(config));
+  });
+
+  it("Initializes a collection keyed by its creator", async () => {
+    const signer = await helpers.getSigner();
+    const creator = anchor.web3.Keypair.generate();
+    const creatorCollectionPda = helpers.findCollectionAddress(
+      creator.publicKey
+    );
+
+    await program.methods
+      .initUncertifiedCollection({ creator: {} }, creator.publicKey, {
+        loanEnabled: true,
+        loanBasisPoints: 200,
+        optionEnabled: true,
+        optionBasisPoints: 200,
+        rentalEnabled: false,
+        rentalBasisPoints: 0,
+        protocolFeeBasisPoints: null,
+        royaltyPolicy: { fixed: {} },
+        verifiedCreatorsOnly: false,
+        payout: null,
+        minLoanAmount: null,
+        maxLoanAmount: null,
+        maxLoanDuration: null,
+        maxLoanBasisPoints: null,
+        maxOptionExpiry: null,
+        permissionless: false,
+      })
+      .accounts({
+        signer: signer.publicKey,
+        globalConfig: helpers.findGlobalConfigAddress(),
+        authority: authority.publicKey,
+        collection: creatorCollectionPda,
+      })
+      .signers([signer])
+      .rpc();
+
+    const collectonData = await program.account.collection.fetch(
+      creatorCollectionPda
+    );
+    assert.ok(collectonData.mint.equals(creator.publicKey));
+    assert.deepEqual(collectonData.collectionType, { creator: {} });
+  });
+
+  it("Denylists mints in a collection", async () => {
+    const signer = await helpers.getSigner();
+    const denylistPda = helpers.findDenylistAddress(collectionPda);
+    const mints = [
+      anchor.web3.Keypair.generate().publicKey,
+      anchor.web3.Keypair.generate().publicKey,
+    ];
+
+    await program.methods
+      .initDenylist()
+      .accounts({
+        signer: signer.publicKey,
+        globalConfig: helpers.findGlobalConfigAddress(),
+        authority: authority.publicKey,
+        collection: collectionPda,
+        denylist: denylistPda,
+      })
+      .signers([signer])
+      .rpc();
+
+    await program.methods
+      .updateDenylist(mints, [])
+      .accounts({
+        signer: signer.publicKey,
+        globalConfig: helpers.findGlobalConfigAddress(),
+        authority: authority.publicKey,
+        collection: collectionPda,
+        denylist: denylistPda,
+      })
+      .signers([signer])
+      .rpc();
+
+    let denylist = await program.account.denylist.fetch(denylistPda);
+    assert.equal(denylist.mints.length, 2);
+
+    await program.methods
+      .updateDenylist([], [mints[0]])
+      .accounts({
+        signer: signer.publicKey,
+        globalConfig: helpers.findGlobalConfigAddress(),
+        authority: authority.publicKey,
+        collection: collectionPda,
+        denylist: denylistPda,
+      })
+      .signers([signer])
+      .rpc();
+
+    denylist = await program.account.denylist.fetch(denylistPda);
+    assert.equal(denylist.mints.length, 1);
+    assert.ok(denylist.mints[0].equals(mints[1]));
   });
 
   it("closes a collection", async () => {
@@ -77,9 +196,9 @@ describe("Collections", async () => {
       .closeCollection()
       .accounts({
         signer: signer.publicKey,
+        globalConfig: helpers.findGlobalConfigAddress(),
         authority: authority.publicKey,
         collection: collectionPda,
-        mint: collectionMint.address,
       })
       .signers([signer])
       .rpc();
@@ -92,4 +211,60 @@ describe("Collections", async () => {
       assert.ok(err.message.includes("Account does not exist"));
     }
   });
+
+  it("Migrates a legacy collection", async () => {
+    const signer = await helpers.getSigner();
+    // Loaded into the validator from tests/fixtures
+    const legacyMint = new anchor.web3.PublicKey(
+      "vu6Hzu74YdbM2SwrpALrLQo2GocptwBRHVU56ecDeq1"
+    );
+    const legacyCollectionPda = helpers.findCollectionAddress(legacyMint);
+
+    const legacyAccount = await connection.getAccountInfo(legacyCollectionPda);
+    assert.equal(legacyAccount.data.length, 146, "legacy layout");
+
+    await program.methods
+      .migrateCollection()
+      .accounts({
+        signer: signer.publicKey,
+        globalConfig: helpers.findGlobalConfigAddress(),
+        authority: authority.publicKey,
+        collection: legacyCollectionPda,
+        mint: legacyMint,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc();
+
+    const collectonData = await program.account.collection.fetch(
+      legacyCollectionPda
+    );
+    assert.ok(
+      collectonData.authority.equals(
+        new anchor.web3.PublicKey(
+          "Fuuqk28ie5YzqkN4Ms93Nahsf8xzrVzjtwdcqsjXhudk"
+        )
+      )
+    );
+    assert.ok(collectonData.mint.equals(legacyMint));
+    assert.deepEqual(collectonData.collectionType, { certified: {} });
+    assert.deepEqual(collectonData.config, {
+      loanEnabled: true,
+      loanBasisPoints: 200,
+      optionEnabled: true,
+      optionBasisPoints: 200,
+      rentalEnabled: false,
+      rentalBasisPoints: 0,
+      protocolFeeBasisPoints: null,
+      royaltyPolicy: { fixed: {} },
+      verifiedCreatorsOnly: false,
+      payout: null,
+      minLoanAmount: null,
+      maxLoanAmount: null,
+      maxLoanDuration: null,
+      maxLoanBasisPoints: null,
+      maxOptionExpiry: null,
+      permissionless: false,
+    });
+  });
 });
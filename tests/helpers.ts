@@ -1,6 +1,7 @@
 import * as anchor from "@project-serum/anchor";
 import * as splToken from "@solana/spl-token";
 import * as bip39 from "bip39";
+import { createHash } from "crypto";
 import { derivePath } from "ed25519-hd-key";
 import { Metaplex, keypairIdentity, Token } from "@metaplex-foundation/js";
 import {
@@ -35,6 +36,31 @@ export async function getAuthority() {
   return fromMnemomic(mnemomic);
 }
 
+// Signs an attestation over the instruction so it can be sent without the co-signer. The
+// instruction must already include the receipt derived from the same nonce
+export async function attest(
+  instruction: anchor.web3.TransactionInstruction,
+  expiry: number,
+  nonce: bigint
+): Promise<anchor.web3.TransactionInstruction> {
+  const signer = await getSigner();
+
+  const hash = createHash("sha256");
+  hash.update(instruction.programId.toBuffer());
+  instruction.keys.forEach((key) => hash.update(key.pubkey.toBuffer()));
+  hash.update(instruction.data);
+
+  const message = Buffer.alloc(48);
+  hash.digest().copy(message, 0);
+  message.writeBigInt64LE(BigInt(expiry), 32);
+  message.writeBigUInt64LE(nonce, 40);
+
+  return anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+    privateKey: signer.secretKey,
+    message,
+  });
+}
+
 export function getProgram(
   provider: anchor.AnchorProvider
 ): anchor.Program<OndaListings> {
@@ -79,6 +105,17 @@ export function findCollectionAddress(
   return collectionAddress;
 }
 
+export function findDenylistAddress(
+  collection: anchor.web3.PublicKey
+): anchor.web3.PublicKey {
+  const [denylistAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("denylist"), collection.toBuffer()],
+    PROGRAM_ID
+  );
+
+  return denylistAddress;
+}
+
 export function findTokenManagerAddress(
   mint: anchor.web3.PublicKey
 ): anchor.web3.PublicKey {
@@ -92,16 +129,113 @@ export function findTokenManagerAddress(
 
 export function findLoanAddress(
   mint: anchor.web3.PublicKey,
-  borrower: anchor.web3.PublicKey
+  borrower: anchor.web3.PublicKey,
+  sequence: number
 ): anchor.web3.PublicKey {
   const [loanAddress] = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("loan"), mint.toBuffer(), borrower.toBuffer()],
+    [
+      Buffer.from("loan"),
+      mint.toBuffer(),
+      borrower.toBuffer(),
+      new anchor.BN(sequence).toArrayLike(Buffer, "le", 8),
+    ],
     PROGRAM_ID
   );
 
   return loanAddress;
 }
 
+export function findTreasuryAddress(): anchor.web3.PublicKey {
+  const [treasuryAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    PROGRAM_ID
+  );
+
+  return treasuryAddress;
+}
+
+export function findGlobalConfigAddress(): anchor.web3.PublicKey {
+  const [globalConfigAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("global_config")],
+    PROGRAM_ID
+  );
+
+  return globalConfigAddress;
+}
+
+export function findProgramDataAddress(): anchor.web3.PublicKey {
+  const [programDataAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [PROGRAM_ID.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
+  return programDataAddress;
+}
+
+export async function findAttestationReceiptAddress(
+  nonce: bigint
+): Promise<anchor.web3.PublicKey> {
+  const signer = await getSigner();
+  const nonceBuffer = Buffer.alloc(8);
+  nonceBuffer.writeBigUInt64LE(nonce);
+
+  const [attestationReceiptAddress] =
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("attestation_receipt"),
+        signer.publicKey.toBuffer(),
+        nonceBuffer,
+      ],
+      PROGRAM_ID
+    );
+
+  return attestationReceiptAddress;
+}
+
+export function findReferrerStatsAddress(
+  referrer: anchor.web3.PublicKey
+): anchor.web3.PublicKey {
+  const [referrerStatsAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("referrer_stats"), referrer.toBuffer()],
+    PROGRAM_ID
+  );
+
+  return referrerStatsAddress;
+}
+
+export function findLenderRiskAddress(
+  lender: anchor.web3.PublicKey
+): anchor.web3.PublicKey {
+  const [lenderRiskAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("lender_risk"), lender.toBuffer()],
+    PROGRAM_ID
+  );
+
+  return lenderRiskAddress;
+}
+
+export function findLoanAuctionVaultAddress(
+  loan: anchor.web3.PublicKey
+): anchor.web3.PublicKey {
+  const [vaultAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("loan_auction_vault"), loan.toBuffer()],
+    PROGRAM_ID
+  );
+
+  return vaultAddress;
+}
+
+export function findOfferCounterAddress(
+  owner: anchor.web3.PublicKey
+): anchor.web3.PublicKey {
+  const [offerCounterAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("offer_counter"), owner.toBuffer()],
+    PROGRAM_ID
+  );
+
+  return offerCounterAddress;
+}
+
 export function findLoanOfferAddress(
   collectionMint: anchor.web3.PublicKey,
   lender: anchor.web3.PublicKey,
@@ -112,7 +246,7 @@ export function findLoanOfferAddress(
       Buffer.from("loan_offer"),
       collectionMint.toBuffer(),
       lender.toBuffer(),
-      new anchor.BN(id).toArrayLike(Buffer),
+      new anchor.BN(id).toArrayLike(Buffer, "le", 8),
     ],
     PROGRAM_ID
   );
@@ -141,7 +275,7 @@ export function findCallOptionBidAddress(
       Buffer.from("call_option_bid"),
       collectionMint.toBuffer(),
       buyer.toBuffer(),
-      new anchor.BN(id).toArrayLike(Buffer),
+      new anchor.BN(id).toArrayLike(Buffer, "le", 8),
     ],
     PROGRAM_ID
   );
@@ -162,10 +296,16 @@ export function findCallOptionBidVaultAddress(
 
 export function findCallOptionAddress(
   mint: anchor.web3.PublicKey,
-  seller: anchor.web3.PublicKey
+  seller: anchor.web3.PublicKey,
+  sequence: number
 ): anchor.web3.PublicKey {
   const [callOptionAddress] = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("call_option"), mint.toBuffer(), seller.toBuffer()],
+    [
+      Buffer.from("call_option"),
+      mint.toBuffer(),
+      seller.toBuffer(),
+      new anchor.BN(sequence).toArrayLike(Buffer, "le", 8),
+    ],
     PROGRAM_ID
   );
 
@@ -234,10 +374,16 @@ export function findTokenRecordAddress(
   return tokenRecordAddress;
 }
 
+export type CollectionOptions = {
+  config?: Record<string, unknown>;
+  creators?: { address: anchor.web3.PublicKey; share: number }[];
+};
+
 export async function mintNFT(
   connection: anchor.web3.Connection,
   keypair: anchor.web3.Keypair,
-  tokenStandard: TokenStandard = TokenStandard.ProgrammableNonFungible
+  tokenStandard: TokenStandard = TokenStandard.ProgrammableNonFungible,
+  collectionOptions: CollectionOptions = {}
 ) {
   const authority = await getAuthority();
   const signer = await getSigner();
@@ -274,9 +420,21 @@ export async function mintNFT(
       loanBasisPoints: 200,
       optionBasisPoints: 200,
       rentalBasisPoints: 200,
+      protocolFeeBasisPoints: null,
+      royaltyPolicy: { fixed: {} },
+      verifiedCreatorsOnly: false,
+      payout: null,
+      minLoanAmount: null,
+      maxLoanAmount: null,
+      maxLoanDuration: null,
+      maxLoanBasisPoints: null,
+      maxOptionExpiry: null,
+      permissionless: false,
+      ...collectionOptions.config,
     })
     .accounts({
       signer: signer.publicKey,
+      globalConfig: findGlobalConfigAddress(),
       authority: authority.publicKey,
       collection: collectionAddress,
       mint: collection.address,
@@ -289,7 +447,7 @@ export async function mintNFT(
     uri: "https://arweave.net/123",
     name: "My NFT",
     sellerFeeBasisPoints: 500,
-    creators: [
+    creators: collectionOptions.creators ?? [
       {
         address: authority.publicKey,
         share: 100,
@@ -352,6 +510,9 @@ export async function askLoan(
     amount: number;
     basisPoints: number;
     duration: number;
+    listingExpiresAt?: number;
+    counterparties?: anchor.web3.PublicKey[];
+    auctionEndsAt?: number;
   }
 ) {
   const keypair = anchor.web3.Keypair.generate();
@@ -364,7 +525,9 @@ export async function askLoan(
 
   const loanAddress = await findLoanAddress(
     nft.mint.address,
-    keypair.publicKey
+    keypair.publicKey,
+    // The NFT is freshly minted, so this is the first position on it
+    1
   );
   const collectionAddress = await findCollectionAddress(
     collection.mint.address
@@ -379,14 +542,20 @@ export async function askLoan(
   const amount = new anchor.BN(options.amount);
   const basisPoints = options.basisPoints;
   const duration = new anchor.BN(options.duration);
+  const listingExpiresAt = options.listingExpiresAt
+    ? new anchor.BN(options.listingExpiresAt)
+    : null;
 
   const accounts = {
     signer: signer.publicKey,
+    attestationReceipt: null,
+    globalConfig: findGlobalConfigAddress(),
     tokenManager,
     depositTokenAccount,
     tokenRecord: null,
     loan: loanAddress,
     collection: collectionAddress,
+    denylist: findDenylistAddress(collectionAddress),
     mint: nft.mint.address,
     borrower: keypair.publicKey,
     edition: nft.edition.address,
@@ -409,7 +578,15 @@ export async function askLoan(
 
   try {
     await program.methods
-      .askLoan(amount, basisPoints, duration)
+      .askLoan(
+        amount,
+        basisPoints,
+        duration,
+        listingExpiresAt,
+        options.counterparties ?? [],
+        options.auctionEndsAt ? new anchor.BN(options.auctionEndsAt) : null,
+        []
+      )
       .accounts(accounts)
       .signers([signer])
       .rpc();
@@ -433,9 +610,59 @@ export async function askLoan(
   };
 }
 
+export async function bidLoan(
+  connection: anchor.web3.Connection,
+  borrower: Awaited<ReturnType<typeof askLoan>>,
+  basisPoints: number,
+  previousLender: anchor.web3.PublicKey = null
+) {
+  const keypair = anchor.web3.Keypair.generate();
+  const signer = await getSigner();
+  const provider = getProvider(connection, keypair);
+  const program = getProgram(provider);
+  await requestAirdrop(connection, keypair.publicKey);
+
+  try {
+    await program.methods
+      .bidLoan(basisPoints)
+      .accounts({
+        signer: signer.publicKey,
+        globalConfig: findGlobalConfigAddress(),
+        borrower: borrower.keypair.publicKey,
+        lender: keypair.publicKey,
+        previousLender,
+        previousLenderRisk: previousLender
+          ? findLenderRiskAddress(previousLender)
+          : null,
+        loan: borrower.loan,
+        escrowPaymentAccount: findLoanAuctionVaultAddress(borrower.loan),
+        lenderRisk: findLenderRiskAddress(keypair.publicKey),
+        mint: borrower.mint,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+      })
+      .signers([signer])
+      .rpc();
+  } catch (error) {
+    console.log(error.logs);
+    throw error;
+  }
+
+  return {
+    keypair,
+    provider,
+    program,
+  };
+}
+
 export async function giveLoan(
   connection: anchor.web3.Connection,
-  borrower: Awaited<ReturnType<typeof askLoan>>
+  borrower: Awaited<ReturnType<typeof askLoan>>,
+  expectedTerms: {
+    amount: anchor.BN;
+    basisPoints: number;
+    duration: anchor.BN;
+  } = null
 ) {
   const keypair = anchor.web3.Keypair.generate();
   const signer = await getSigner();
@@ -443,16 +670,32 @@ export async function giveLoan(
   const program = getProgram(provider);
   await requestAirdrop(connection, keypair.publicKey);
 
+  const loan = await program.account.loan.fetch(borrower.loan);
+
   try {
     await program.methods
-      .giveLoan()
+      .giveLoan(
+        expectedTerms ?? {
+          amount: loan.amount,
+          basisPoints: loan.basisPoints,
+          duration: loan.duration,
+        }
+      )
       .accounts({
         signer: signer.publicKey,
+        attestationReceipt: null,
+        globalConfig: findGlobalConfigAddress(),
         tokenManager: borrower.tokenManager,
         loan: borrower.loan,
         borrower: borrower.keypair.publicKey,
         lender: keypair.publicKey,
+        lenderRisk: findLenderRiskAddress(keypair.publicKey),
+        collection: loan.collection,
+        treasury: findTreasuryAddress(),
+        referrer: null,
+        referrerStats: null,
         mint: borrower.mint,
+        sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: splToken.TOKEN_PROGRAM_ID,
         clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
@@ -480,8 +723,15 @@ export async function offerLoan(
     amount: number;
     basisPoints: number;
     duration: number;
+    expiresAt?: number;
+    mint?: anchor.web3.PublicKey;
+    merkleRoot?: number[];
+    collections?: anchor.web3.PublicKey[];
+    counterparties?: anchor.web3.PublicKey[];
+    rateCurve?: { duration: number; basisPoints: number }[];
     tokenStandard?: TokenStandard;
-  }
+  },
+  collectionOptions: CollectionOptions = {}
 ) {
   const keypair = anchor.web3.Keypair.generate();
   const signer = await getSigner();
@@ -492,12 +742,15 @@ export async function offerLoan(
   const { nft, collection } = await mintNFT(
     connection,
     keypair,
-    options.tokenStandard
+    options.tokenStandard,
+    collectionOptions
   );
 
   const amount = new anchor.BN(options.amount);
   const basisPoints = options.basisPoints;
   const duration = new anchor.BN(options.duration);
+  const expiresAt = options.expiresAt ? new anchor.BN(options.expiresAt) : null;
+  // The keypair is new, so its offer counter starts at zero
   const id = 0;
 
   const loanOffer = await findLoanOfferAddress(
@@ -512,12 +765,28 @@ export async function offerLoan(
 
   try {
     await program.methods
-      .offerLoan(amount, basisPoints, duration, id)
+      .offerLoan(
+        amount,
+        basisPoints,
+        duration,
+        expiresAt,
+        options.mint ?? null,
+        options.merkleRoot ?? null,
+        options.collections ?? [],
+        options.counterparties ?? [],
+        (options.rateCurve ?? []).map((point) => ({
+          duration: new anchor.BN(point.duration),
+          basisPoints: point.basisPoints,
+        }))
+      )
       .accounts({
+        offerCounter: findOfferCounterAddress(keypair.publicKey),
         loanOffer,
         escrowPaymentAccount,
         collection: collectionAddress,
         signer: signer.publicKey,
+        attestationReceipt: null,
+        globalConfig: findGlobalConfigAddress(),
         lender: keypair.publicKey,
       })
       .signers([signer])
@@ -541,7 +810,13 @@ export async function offerLoan(
 
 export async function takeLoan(
   connection: anchor.web3.Connection,
-  lender: LoanOfferLender
+  lender: LoanOfferLender,
+  options: {
+    collateralCollection?: anchor.web3.PublicKey;
+    duration?: number;
+    basisPoints?: number;
+    referrer?: anchor.web3.PublicKey;
+  } = {}
 ) {
   const keypair = anchor.web3.Keypair.generate();
   const signer = await getSigner();
@@ -564,19 +839,34 @@ export async function takeLoan(
 
   const loanAddress = await findLoanAddress(
     lender.nft.mint.address,
-    keypair.publicKey
+    keypair.publicKey,
+    // The NFT is freshly minted, so this is the first position on it
+    1
   );
   const tokenManager = await findTokenManagerAddress(lender.nft.mint.address);
+  const offer = await program.account.loanOffer.fetch(lender.loanOffer);
 
   const accounts = {
     signer: signer.publicKey,
+    attestationReceipt: null,
+    globalConfig: findGlobalConfigAddress(),
     tokenManager,
     depositTokenAccount,
     loan: loanAddress,
     loanOffer: lender.loanOffer,
     collection: lender.collection,
+    collateralCollection: options.collateralCollection ?? null,
+    denylist: findDenylistAddress(
+      options.collateralCollection ?? lender.collection
+    ),
     escrowPaymentAccount: lender.escrowPaymentAccount,
     lender: lender.keypair.publicKey,
+    lenderRisk: findLenderRiskAddress(lender.keypair.publicKey),
+    treasury: findTreasuryAddress(),
+    referrer: options.referrer ?? null,
+    referrerStats: options.referrer
+      ? findReferrerStatsAddress(options.referrer)
+      : null,
     borrower: keypair.publicKey,
     mint: lender.nft.mint.address,
     metadata: lender.nft.metadataAddress,
@@ -599,7 +889,19 @@ export async function takeLoan(
 
   try {
     await program.methods
-      .takeLoanOffer(0)
+      .takeLoanOffer(
+        new anchor.BN(lender.id),
+        [],
+        options.duration ? new anchor.BN(options.duration) : null,
+        {
+          amount: offer.amount,
+          basisPoints: options.basisPoints ?? offer.basisPoints,
+          duration: options.duration
+            ? new anchor.BN(options.duration)
+            : offer.duration,
+        },
+        []
+      )
       .accounts(accounts)
       .signers([signer])
       .rpc();
@@ -647,6 +949,10 @@ export async function bidCallOption(
     amount: number;
     strikePrice: number;
     expiry: number;
+    expiresAt?: number;
+    mint?: anchor.web3.PublicKey;
+    merkleRoot?: number[];
+    counterparties?: anchor.web3.PublicKey[];
   }
 ) {
   const keypair = anchor.web3.Keypair.generate();
@@ -660,6 +966,8 @@ export async function bidCallOption(
   const amount = new anchor.BN(options.amount);
   const strikePrice = new anchor.BN(options.strikePrice);
   const duration = new anchor.BN(options.expiry);
+  const expiresAt = options.expiresAt ? new anchor.BN(options.expiresAt) : null;
+  // The keypair is new, so its offer counter starts at zero
   const id = 0;
 
   const callOptionBid = await findCallOptionBidAddress(
@@ -675,12 +983,23 @@ export async function bidCallOption(
   );
 
   await program.methods
-    .bidCallOption(amount, strikePrice, duration, id)
+    .bidCallOption(
+      amount,
+      strikePrice,
+      duration,
+      expiresAt,
+      options.mint ?? null,
+      options.merkleRoot ?? null,
+      options.counterparties ?? []
+    )
     .accounts({
+      offerCounter: findOfferCounterAddress(keypair.publicKey),
       callOptionBid,
       escrowPaymentAccount,
       collection: collectionAddress,
       signer: signer.publicKey,
+      attestationReceipt: null,
+      globalConfig: findGlobalConfigAddress(),
       buyer: keypair.publicKey,
     })
     .signers([signer])
@@ -725,21 +1044,39 @@ export async function sellCallOption(
 
   const callOptionAddress = await findCallOptionAddress(
     buyer.nft.mint.address,
-    keypair.publicKey
+    keypair.publicKey,
+    // The NFT is freshly minted, so this is the first position on it
+    1
   );
   const tokenManager = await findTokenManagerAddress(buyer.nft.mint.address);
+  const bid = await program.account.callOptionBid.fetch(buyer.callOptionBid);
 
   try {
     await program.methods
-      .sellCallOption(0)
+      .sellCallOption(
+        new anchor.BN(buyer.id),
+        [],
+        {
+          amount: bid.amount,
+          strikePrice: bid.strikePrice,
+          expiry: bid.expiry,
+        },
+        []
+      )
       .accounts({
         signer: signer.publicKey,
+        attestationReceipt: null,
+        globalConfig: findGlobalConfigAddress(),
         tokenManager,
         depositTokenAccount,
         callOption: callOptionAddress,
         callOptionBid: buyer.callOptionBid,
         collection: buyer.collection,
+        denylist: findDenylistAddress(buyer.collection),
         escrowPaymentAccount: buyer.escrowPaymentAccount,
+        treasury: findTreasuryAddress(),
+        referrer: null,
+        referrerStats: null,
         buyer: buyer.keypair.publicKey,
         seller: keypair.publicKey,
         mint: buyer.nft.mint.address,
@@ -775,21 +1112,31 @@ export async function askCallOption(
     amount: number;
     strikePrice: number;
     expiry: number;
-  }
+    listingExpiresAt?: number;
+    counterparties?: anchor.web3.PublicKey[];
+  },
+  collectionOptions: CollectionOptions = {}
 ) {
   const keypair = anchor.web3.Keypair.generate();
   const signer = await getSigner();
   const provider = getProvider(connection, keypair);
   const program = getProgram(provider);
   await requestAirdrop(connection, keypair.publicKey);
-  const { nft, collection } = await mintNFT(connection, keypair);
+  const { nft, collection } = await mintNFT(
+    connection,
+    keypair,
+    TokenStandard.ProgrammableNonFungible,
+    collectionOptions
+  );
   const largestAccounts = await connection.getTokenLargestAccounts(
     nft.mint.address
   );
   const depositTokenAccount = largestAccounts.value[0].address;
   const callOptionAddress = await findCallOptionAddress(
     nft.mint.address,
-    keypair.publicKey
+    keypair.publicKey,
+    // The NFT is freshly minted, so this is the first position on it
+    1
   );
   const collectionAddress = await findCollectionAddress(
     collection.mint.address
@@ -799,12 +1146,18 @@ export async function askCallOption(
   const amount = new anchor.BN(options.amount);
   const strikePrice = new anchor.BN(options.strikePrice);
   const expiry = new anchor.BN(options.expiry);
+  const listingExpiresAt = options.listingExpiresAt
+    ? new anchor.BN(options.listingExpiresAt)
+    : null;
 
   const accounts = {
     tokenManager,
     signer: signer.publicKey,
+    attestationReceipt: null,
+    globalConfig: findGlobalConfigAddress(),
     callOption: callOptionAddress,
     collection: collectionAddress,
+    denylist: findDenylistAddress(collectionAddress),
     mint: nft.mint.address,
     metadata: nft.metadataAddress,
     edition: nft.edition.address,
@@ -816,6 +1169,8 @@ export async function askCallOption(
     authorizationRulesProgram: AUTHORIZATION_RULES_PROGRAM_ID,
     tokenProgram: splToken.TOKEN_PROGRAM_ID,
     rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+    sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
     systemProgram: anchor.web3.SystemProgram.programId,
     sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
     clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
@@ -830,7 +1185,14 @@ export async function askCallOption(
 
   try {
     await program.methods
-      .askCallOption(amount, strikePrice, expiry)
+      .askCallOption(
+        amount,
+        strikePrice,
+        expiry,
+        listingExpiresAt,
+        options.counterparties ?? [],
+        []
+      )
       .accounts(accounts)
       .signers([signer])
       .rpc();
@@ -868,9 +1230,14 @@ export async function buyCallOption(
     connection,
     seller.metadata
   );
+  const callOption = await program.account.callOption.fetch(
+    seller.callOption
+  );
 
   const accounts = {
     signer: signer.publicKey,
+    attestationReceipt: null,
+    globalConfig: findGlobalConfigAddress(),
     seller: seller.keypair.publicKey,
     buyer: keypair.publicKey,
     callOption: seller.callOption,
@@ -879,6 +1246,9 @@ export async function buyCallOption(
     metadata: seller.metadata,
     edition: seller.edition,
     collection: seller.collection,
+    treasury: findTreasuryAddress(),
+    referrer: null,
+    referrerStats: null,
     metadataProgram: METADATA_PROGRAM_ID,
     systemProgram: anchor.web3.SystemProgram.programId,
     tokenProgram: splToken.TOKEN_PROGRAM_ID,
@@ -887,14 +1257,34 @@ export async function buyCallOption(
 
   try {
     const signature = await program.methods
-      .buyCallOption()
+      .buyCallOption({
+        amount: callOption.amount,
+        strikePrice: callOption.strikePrice,
+        expiry: callOption.expiry,
+      })
       .accounts(accounts)
       .remainingAccounts(
-        metadata.data.creators.map((creator) => ({
-          pubkey: creator.address,
-          isSigner: false,
-          isWritable: true,
-        }))
+        metadata.data.creators
+          .filter(
+            (creator) => creator.verified || !callOption.verifiedCreatorsOnly
+          )
+          .map((creator) => ({
+            pubkey: creator.address,
+            isSigner: false,
+            isWritable: true,
+          }))
+          // The payout account follows the creators
+          .concat(
+            callOption.payout
+              ? [
+                  {
+                    pubkey: callOption.payout.address,
+                    isSigner: false,
+                    isWritable: true,
+                  },
+                ]
+              : []
+          )
       )
       .signers([signer])
       .rpc();
@@ -964,6 +1354,7 @@ export async function initRental(
         rental,
         tokenManager,
         signer: signer.publicKey,
+        globalConfig: findGlobalConfigAddress(),
         collection: collectionAddress,
         lender: keypair.publicKey,
         depositTokenAccount: depositTokenAccount,
@@ -971,6 +1362,7 @@ export async function initRental(
         mint: nft.mint.address,
         edition: nft.edition.address,
         metadataProgram: METADATA_PROGRAM_ID,
+        sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: splToken.TOKEN_PROGRAM_ID,
         clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
@@ -1023,6 +1415,7 @@ export async function takeRental(
       .takeRental(days)
       .accounts({
         signer: signer.publicKey,
+        globalConfig: findGlobalConfigAddress(),
         borrower: keypair.publicKey,
         lender: lender.keypair.publicKey,
         rental: lender.rental,
@@ -1071,6 +1464,7 @@ export async function recoverRental(
       .recoverRental()
       .accounts({
         signer: signer.publicKey,
+        globalConfig: findGlobalConfigAddress(),
         borrower: borrower.keypair.publicKey,
         lender: lender.keypair.publicKey,
         rental: lender.rental,
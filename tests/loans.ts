@@ -7,6 +7,7 @@ import {
   PROGRAM_ID as METADATA_PROGRAM_ID,
 } from "@metaplex-foundation/mpl-token-metadata";
 import { PROGRAM_ID as AUTHORIZATION_RULES_PROGRAM_ID } from "@metaplex-foundation/mpl-token-auth-rules";
+import { Metaplex, keypairIdentity } from "@metaplex-foundation/js";
 import * as anchor from "@project-serum/anchor";
 import * as splToken from "@solana/spl-token";
 import * as helpers from "./helpers";
@@ -85,6 +86,288 @@ describe("Loans", () => {
       );
     });
 
+    it("Will not allow a mint-specific offer to be taken with another mint", async () => {
+      const offer = await helpers.offerLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 86_400,
+        mint: anchor.web3.Keypair.generate().publicKey,
+      });
+
+      try {
+        await helpers.takeLoan(connection, offer);
+        assert.fail("Mint-specific offer was taken with another mint!");
+      } catch (err) {
+        assert(err instanceof anchor.AnchorError);
+        assert.equal(err.error.errorCode.code, "InvalidMint");
+      }
+    });
+
+    it("Will not allow an allowlisted offer to be taken without a valid proof", async () => {
+      const offer = await helpers.offerLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 86_400,
+        merkleRoot: Array.from(anchor.web3.Keypair.generate().publicKey.toBytes()),
+      });
+
+      try {
+        await helpers.takeLoan(connection, offer);
+        assert.fail("Allowlisted offer was taken without a valid proof!");
+      } catch (err) {
+        assert(err instanceof anchor.AnchorError);
+        assert.equal(err.error.errorCode.code, "InvalidProof");
+      }
+    });
+
+    it("Allows an offer to be taken with a token from an additional collection", async () => {
+      const authority = await helpers.getAuthority();
+      const other = await helpers.mintNFT(connection, authority);
+      const otherCollection = await helpers.findCollectionAddress(
+        other.collection.mint.address
+      );
+      const offer = await helpers.offerLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 86_400,
+        collections: [otherCollection],
+      });
+
+      // Hand the token to the lender so the helper can pass it to the borrower
+      await Metaplex.make(connection)
+        .use(keypairIdentity(authority))
+        .nfts()
+        .transfer({
+          nftOrSft: other.nft,
+          toOwner: offer.keypair.publicKey,
+        });
+
+      const borrower = await helpers.takeLoan(
+        connection,
+        { ...offer, nft: other.nft },
+        { collateralCollection: otherCollection }
+      );
+      const loan = await offer.program.account.loan.fetch(borrower.loan);
+
+      assert.ok(loan.mint.equals(other.nft.mint.address), "mint");
+      assert.deepEqual(loan.state, { active: {} });
+    });
+
+    it("Records referrals on the referrer's stats", async () => {
+      const referrer = anchor.web3.Keypair.generate();
+      const signer = await helpers.getSigner();
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, referrer)
+      );
+      await helpers.requestAirdrop(connection, referrer.publicKey);
+      const referrerStats = helpers.findReferrerStatsAddress(
+        referrer.publicKey
+      );
+
+      await program.methods
+        .initReferrerStats()
+        .accounts({
+          signer: signer.publicKey,
+          globalConfig: helpers.findGlobalConfigAddress(),
+          referrer: referrer.publicKey,
+          referrerStats,
+        })
+        .signers([signer])
+        .rpc();
+
+      const offer = await helpers.offerLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 86_400,
+      });
+      await helpers.takeLoan(connection, offer, {
+        referrer: referrer.publicKey,
+      });
+
+      const stats = await program.account.referrerStats.fetch(referrerStats);
+      assert.ok(stats.referrer.equals(referrer.publicKey), "referrer");
+      assert.equal(stats.referrals.toNumber(), 1, "referrals");
+
+      await program.methods
+        .claimReferralFees()
+        .accounts({
+          referrer: referrer.publicKey,
+          referrerStats,
+        })
+        .rpc();
+
+      const statsAccountInfo = await connection.getAccountInfo(referrerStats);
+      const rentExemptMinimum =
+        await connection.getMinimumBalanceForRentExemption(
+          statsAccountInfo.data.length
+        );
+      assert.equal(
+        statsAccountInfo.lamports,
+        rentExemptMinimum,
+        "Accrued fees should be claimed"
+      );
+    });
+
+    it("Takes a term-structure offer at the chosen duration", async () => {
+      const offer = await helpers.offerLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 86_400,
+        rateCurve: [
+          { duration: 86_400, basisPoints: 500 },
+          { duration: 86_400 * 3, basisPoints: 1_000 },
+        ],
+      });
+
+      const borrower = await helpers.takeLoan(connection, offer, {
+        duration: 86_400 * 2,
+        basisPoints: 750,
+      });
+      const loan = await offer.program.account.loan.fetch(borrower.loan);
+
+      assert.equal(loan.duration.toNumber(), 86_400 * 2, "duration");
+      assert.equal(loan.basisPoints, 750, "basisPoints");
+    });
+
+    it("Will not allow an offer to be taken beyond the lender's limits", async () => {
+      const signer = await helpers.getSigner();
+      const offer = await helpers.offerLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 86_400,
+      });
+
+      await offer.program.methods
+        .initLenderRisk({
+          maxOutstanding: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 2),
+          maxCollectionOutstanding: null,
+          maxActiveLoans: null,
+        })
+        .accounts({
+          signer: signer.publicKey,
+          globalConfig: helpers.findGlobalConfigAddress(),
+          lender: offer.keypair.publicKey,
+          lenderRisk: helpers.findLenderRiskAddress(offer.keypair.publicKey),
+        })
+        .signers([signer])
+        .rpc();
+
+      try {
+        await helpers.takeLoan(connection, offer);
+        assert.fail("Offer was taken beyond the lender's limits!");
+      } catch (err) {
+        assert(err instanceof anchor.AnchorError);
+        assert.equal(err.error.errorCode.code, "ExposureLimitExceeded");
+      }
+    });
+
+    it("Updates an offer", async () => {
+      const signer = await helpers.getSigner();
+      const offer = await helpers.offerLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 86_400,
+      });
+      const vaultBalanceBefore = await connection.getBalance(
+        offer.escrowPaymentAccount
+      );
+      const expiresAt = Math.round(Date.now() / 1000) + 86_400;
+
+      await offer.program.methods
+        .updateLoanOffer(
+          new anchor.BN(offer.id),
+          new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 2),
+          400,
+          new anchor.BN(86_400 * 2),
+          new anchor.BN(expiresAt)
+        )
+        .accounts({
+          signer: signer.publicKey,
+          globalConfig: helpers.findGlobalConfigAddress(),
+          lender: offer.keypair.publicKey,
+          loanOffer: offer.loanOffer,
+          escrowPaymentAccount: offer.escrowPaymentAccount,
+          collection: offer.collection,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .signers([signer])
+        .rpc();
+
+      const loanOffer = await offer.program.account.loanOffer.fetch(
+        offer.loanOffer
+      );
+      const vaultBalanceAfter = await connection.getBalance(
+        offer.escrowPaymentAccount
+      );
+
+      assert.equal(loanOffer.amount.toNumber(), anchor.web3.LAMPORTS_PER_SOL / 2);
+      assert.equal(loanOffer.basisPoints, 400);
+      assert.equal(loanOffer.duration.toNumber(), 86_400 * 2);
+      assert.equal(loanOffer.expiresAt.toNumber(), expiresAt);
+      assert.equal(
+        vaultBalanceBefore - vaultBalanceAfter,
+        anchor.web3.LAMPORTS_PER_SOL / 2,
+        "withdrawn"
+      );
+    });
+
+    it("Will not allow an offer to be updated with a zero duration", async () => {
+      const signer = await helpers.getSigner();
+      const offer = await helpers.offerLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 86_400,
+      });
+
+      try {
+        await offer.program.methods
+          .updateLoanOffer(
+            new anchor.BN(offer.id),
+            new anchor.BN(anchor.web3.LAMPORTS_PER_SOL),
+            500,
+            new anchor.BN(0),
+            null
+          )
+          .accounts({
+            signer: signer.publicKey,
+            globalConfig: helpers.findGlobalConfigAddress(),
+            lender: offer.keypair.publicKey,
+            loanOffer: offer.loanOffer,
+            escrowPaymentAccount: offer.escrowPaymentAccount,
+            collection: offer.collection,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .signers([signer])
+          .rpc();
+        assert.fail("Offer was updated with a zero duration!");
+      } catch (err) {
+        assert(err instanceof anchor.AnchorError);
+        assert.equal(err.error.errorCode.code, "InvalidDuration");
+      }
+    });
+
+    it("Will not allow an offer outside the collection's limits", async () => {
+      try {
+        await helpers.offerLoan(
+          connection,
+          {
+            amount: anchor.web3.LAMPORTS_PER_SOL * 2,
+            basisPoints: 500,
+            duration: 86_400,
+          },
+          {
+            config: {
+              maxLoanAmount: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL),
+            },
+          }
+        );
+        assert.fail("Offer was made above the collection's maximum amount!");
+      } catch (err) {
+        assert(err instanceof anchor.AnchorError);
+        assert.equal(err.error.errorCode.code, "TermsOutOfBounds");
+      }
+    });
+
     it("Closes an offer", async () => {
       const signer = await helpers.getSigner();
       const offer = await helpers.offerLoan(connection, {
@@ -94,9 +377,10 @@ describe("Loans", () => {
       });
 
       await offer.program.methods
-        .closeLoanOffer(offer.id)
+        .closeLoanOffer(new anchor.BN(offer.id))
         .accounts({
           signer: signer.publicKey,
+          globalConfig: helpers.findGlobalConfigAddress(),
           lender: offer.keypair.publicKey,
           loanOffer: offer.loanOffer,
           escrowPaymentAccount: offer.escrowPaymentAccount,
@@ -107,6 +391,98 @@ describe("Loans", () => {
 
       const accountInfo = await connection.getAccountInfo(offer.loanOffer);
       assert.equal(accountInfo, null, "Loan offer account should be null");
+      const vaultInfo = await connection.getAccountInfo(
+        offer.escrowPaymentAccount
+      );
+      assert.equal(vaultInfo, null, "Vault should be emptied");
+    });
+
+    it("Closes offers in a batch", async () => {
+      const signer = await helpers.getSigner();
+      const offer = await helpers.offerLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 86_400,
+      });
+
+      await offer.program.methods
+        .closeLoanOffers()
+        .accounts({
+          signer: signer.publicKey,
+          globalConfig: helpers.findGlobalConfigAddress(),
+          lender: offer.keypair.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: offer.loanOffer, isSigner: false, isWritable: true },
+          {
+            pubkey: offer.escrowPaymentAccount,
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: offer.collection, isSigner: false, isWritable: false },
+        ])
+        .signers([signer])
+        .rpc();
+
+      const accountInfo = await connection.getAccountInfo(offer.loanOffer);
+      const vaultInfo = await connection.getAccountInfo(
+        offer.escrowPaymentAccount
+      );
+      assert.equal(accountInfo, null, "Loan offer account should be null");
+      assert.equal(vaultInfo, null, "Vault should be emptied");
+    });
+
+    it("Allows anyone to close an expired offer", async () => {
+      const keypair = anchor.web3.Keypair.generate();
+      const provider = helpers.getProvider(connection, keypair);
+      const program = helpers.getProgram(provider);
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+
+      const offer = await helpers.offerLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 86_400,
+        expiresAt: Math.round(Date.now() / 1000) + 2,
+      });
+
+      await helpers.wait(4);
+
+      const lenderBalanceBefore = await connection.getBalance(
+        offer.keypair.publicKey
+      );
+
+      const collection = await program.account.collection.fetch(
+        offer.collection
+      );
+      assert.equal(
+        collection.config.permissionless,
+        false,
+        "The close must not rely on a permissionless collection"
+      );
+
+      await program.methods
+        .closeExpiredLoanOffer(new anchor.BN(offer.id))
+        .accounts({
+          lender: offer.keypair.publicKey,
+          loanOffer: offer.loanOffer,
+          escrowPaymentAccount: offer.escrowPaymentAccount,
+          collection: offer.collection,
+        })
+        .rpc();
+
+      const accountInfo = await connection.getAccountInfo(offer.loanOffer);
+      const lenderBalanceAfter = await connection.getBalance(
+        offer.keypair.publicKey
+      );
+      const vaultInfo = await connection.getAccountInfo(
+        offer.escrowPaymentAccount
+      );
+      assert.equal(accountInfo, null, "Loan offer account should be null");
+      assert.equal(vaultInfo, null, "Vault should be emptied");
+      assert.ok(
+        lenderBalanceAfter > lenderBalanceBefore + anchor.web3.LAMPORTS_PER_SOL,
+        "lender refunded"
+      );
     });
   });
 
@@ -199,7 +575,6 @@ describe("Loans", () => {
     it("Will only allow lender to repossess an overdue loan", async () => {
       // Creates another signer
       const keypair = anchor.web3.Keypair.generate();
-      const signer = await helpers.getSigner();
       const provider = helpers.getProvider(connection, keypair);
       const program = helpers.getProgram(provider);
       await helpers.requestAirdrop(connection, keypair.publicKey);
@@ -216,11 +591,11 @@ describe("Loans", () => {
         await program.methods
           .repossess()
           .accounts({
-            signer: signer.publicKey,
             borrower: borrower.keypair.publicKey,
             depositTokenAccount: borrower.depositTokenAccount,
             depositTokenRecord: borrower.tokenRecord,
             lender: lender.keypair.publicKey,
+            lenderRisk: helpers.findLenderRiskAddress(lender.keypair.publicKey),
             escrowTokenAccount,
             escrowTokenRecord,
             loan: borrower.loan,
@@ -238,7 +613,6 @@ describe("Loans", () => {
             clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           })
-          .signers([signer])
           .rpc();
 
         assert.fail("Should not have been able to repossess");
@@ -250,7 +624,6 @@ describe("Loans", () => {
     });
 
     it("Allows an overdue loan to be repossessed by the lender", async () => {
-      const signer = await helpers.getSigner();
       const escrowTokenAccountPda = helpers.findEscrowTokenAccount(
         borrower.tokenManager
       );
@@ -260,11 +633,11 @@ describe("Loans", () => {
       );
 
       const accounts = {
-        signer: signer.publicKey,
         borrower: borrower.keypair.publicKey,
         depositTokenAccount: borrower.depositTokenAccount,
         depositTokenRecord: borrower.tokenRecord,
         lender: lender.keypair.publicKey,
+        lenderRisk: helpers.findLenderRiskAddress(lender.keypair.publicKey),
         escrowTokenAccount: escrowTokenAccountPda,
         escrowTokenRecord,
         loan: borrower.loan,
@@ -288,7 +661,6 @@ describe("Loans", () => {
         await lender.program.methods
           .repossess()
           .accounts(accounts)
-          .signers([signer])
           .rpc();
       } catch (err) {
         console.log(err);
@@ -348,6 +720,7 @@ describe("Loans", () => {
 
       const accounts = {
         signer: signer.publicKey,
+        globalConfig: helpers.findGlobalConfigAddress(),
         authority: lender.keypair.publicKey,
         destinationTokenAccount: lenderTokenAccountAddress,
         destinationTokenRecord: lenderTokenRecord,
@@ -386,13 +759,10 @@ describe("Loans", () => {
     });
 
     it("Will allow accounts to be closed once overdue loans are repossessed", async () => {
-      const signer = await helpers.getSigner();
-
       try {
         await borrower.program.methods
           .closeLoan()
           .accounts({
-            signer: signer.publicKey,
             borrower: borrower.keypair.publicKey,
             depositTokenAccount: borrower.depositTokenAccount,
             depositTokenRecord: borrower.tokenRecord,
@@ -408,7 +778,6 @@ describe("Loans", () => {
             sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
             systemProgram: anchor.web3.SystemProgram.programId,
           })
-          .signers([signer])
           .rpc();
       } catch (err) {
         console.log(err.logs);
@@ -426,6 +795,466 @@ describe("Loans", () => {
     });
   });
 
+  describe("Reclaiming settled loans", () => {
+    let borrower: helpers.LoanBorrower;
+    let lender: helpers.LoanLender;
+
+    it("Repossesses a defaulted loan", async () => {
+      borrower = await helpers.askLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL / 100,
+        basisPoints: 500,
+        duration: 1,
+      });
+      lender = await helpers.giveLoan(connection, borrower);
+
+      const escrowTokenAccountPda = helpers.findEscrowTokenAccount(
+        borrower.tokenManager
+      );
+
+      await helpers.waitForOverdue(borrower.program, borrower.loan);
+
+      await lender.program.methods
+        .repossess()
+        .accounts({
+          borrower: borrower.keypair.publicKey,
+          depositTokenAccount: borrower.depositTokenAccount,
+          depositTokenRecord: borrower.tokenRecord,
+          lender: lender.keypair.publicKey,
+          lenderRisk: helpers.findLenderRiskAddress(lender.keypair.publicKey),
+          escrowTokenAccount: escrowTokenAccountPda,
+          escrowTokenRecord: helpers.findTokenRecordAddress(
+            borrower.mint,
+            escrowTokenAccountPda
+          ),
+          loan: borrower.loan,
+          tokenManager: borrower.tokenManager,
+          mint: borrower.mint,
+          metadata: borrower.metadata,
+          edition: borrower.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          authorizationRules: null,
+          authorizationRulesProgram: AUTHORIZATION_RULES_PROGRAM_ID,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: splToken.ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+
+      const loan = await borrower.program.account.loan.fetch(borrower.loan);
+      assert.deepEqual(loan.state, { defaulted: {} });
+    });
+
+    it("Allows anyone to reclaim a defaulted loan for a tip", async () => {
+      const tip = 100_000;
+      const keypair = anchor.web3.Keypair.generate();
+      const provider = helpers.getProvider(connection, keypair);
+      const program = helpers.getProgram(provider);
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+
+      // The borrower leaves a tip for whoever cleans up the loan account
+      await borrower.program.provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: borrower.keypair.publicKey,
+            toPubkey: borrower.loan,
+            lamports: tip,
+          })
+        )
+      );
+
+      const loanAccountInfo = await connection.getAccountInfo(borrower.loan);
+      const borrowerPreReclaimBalance = await connection.getBalance(
+        borrower.keypair.publicKey
+      );
+      const keeperPreReclaimBalance = await connection.getBalance(
+        keypair.publicKey
+      );
+
+      const loanAccount = await program.account.loan.fetch(borrower.loan);
+      assert.equal(
+        loanAccount.permissionless,
+        false,
+        "The reclaim must not rely on a permissionless loan"
+      );
+
+      await program.methods
+        .reclaimLoan()
+        .accounts({
+          payer: keypair.publicKey,
+          borrower: borrower.keypair.publicKey,
+          depositTokenAccount: null,
+          depositTokenRecord: null,
+          loan: borrower.loan,
+          tokenManager: borrower.tokenManager,
+          mint: borrower.mint,
+          metadata: borrower.metadata,
+          edition: borrower.edition,
+          authorizationRules: null,
+          authorizationRulesProgram: AUTHORIZATION_RULES_PROGRAM_ID,
+          metadataProgram: METADATA_PROGRAM_ID,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const borrowerPostReclaimBalance = await connection.getBalance(
+        borrower.keypair.publicKey
+      );
+      const keeperPostReclaimBalance = await connection.getBalance(
+        keypair.publicKey
+      );
+      const tokenManager = await borrower.program.account.tokenManager.fetch(
+        borrower.tokenManager
+      );
+
+      assert.equal(await connection.getAccountInfo(borrower.loan), null);
+      assert.equal(
+        borrowerPostReclaimBalance - borrowerPreReclaimBalance,
+        loanAccountInfo.lamports - tip,
+        "Rent returned to the borrower"
+      );
+      // The caller also pays the fee for both signatures
+      assert.equal(
+        keeperPostReclaimBalance - keeperPreReclaimBalance,
+        tip - 10_000,
+        "Tip paid to the caller"
+      );
+      assert.equal(tokenManager.authority, null);
+    });
+  });
+
+  describe("Private listings", () => {
+    it("Will not allow a private listing to be given by another lender", async () => {
+      const borrower = await helpers.askLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL / 100,
+        basisPoints: 500,
+        duration: 86_400,
+        counterparties: [anchor.web3.Keypair.generate().publicKey],
+      });
+
+      try {
+        await helpers.giveLoan(connection, borrower);
+        assert.fail("Private listing was given by another lender!");
+      } catch (err) {
+        assert(err instanceof anchor.AnchorError);
+        assert.equal(err.error.errorCode.code, "InvalidCounterparty");
+      }
+    });
+  });
+
+  describe("Expected terms", () => {
+    it("Will not allow a listing to be given on different terms", async () => {
+      const borrower = await helpers.askLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL / 100,
+        basisPoints: 500,
+        duration: 86_400,
+      });
+
+      try {
+        await helpers.giveLoan(connection, borrower, {
+          amount: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 100),
+          basisPoints: 400,
+          duration: new anchor.BN(86_400),
+        });
+        assert.fail("Listing was given on different terms!");
+      } catch (err) {
+        assert(err instanceof anchor.AnchorError);
+        assert.equal(err.error.errorCode.code, "TermsMismatch");
+      }
+    });
+  });
+
+  describe("Attestations", () => {
+    async function giveLoanInstruction(
+      borrower: helpers.LoanBorrower,
+      nonce: bigint | null
+    ) {
+      const keypair = anchor.web3.Keypair.generate();
+      const provider = helpers.getProvider(connection, keypair);
+      const program = helpers.getProgram(provider);
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+
+      const loan = await program.account.loan.fetch(borrower.loan);
+      const instruction = await program.methods
+        .giveLoan({
+          amount: loan.amount,
+          basisPoints: loan.basisPoints,
+          duration: loan.duration,
+        })
+        .accounts({
+          signer: null,
+          attestationReceipt:
+            nonce === null
+              ? null
+              : await helpers.findAttestationReceiptAddress(nonce),
+          globalConfig: helpers.findGlobalConfigAddress(),
+          tokenManager: borrower.tokenManager,
+          loan: borrower.loan,
+          borrower: borrower.keypair.publicKey,
+          lender: keypair.publicKey,
+          lenderRisk: helpers.findLenderRiskAddress(keypair.publicKey),
+          collection: loan.collection,
+          treasury: helpers.findTreasuryAddress(),
+          referrer: null,
+          referrerStats: null,
+          mint: borrower.mint,
+          sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .instruction();
+
+      return { instruction, provider };
+    }
+
+    it("Gives a loan with an attestation instead of the co-signer", async () => {
+      const borrower = await helpers.askLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL / 100,
+        basisPoints: 500,
+        duration: 86_400,
+      });
+      const nonce = BigInt(Date.now());
+      const { instruction, provider } = await giveLoanInstruction(
+        borrower,
+        nonce
+      );
+      const attestation = await helpers.attest(
+        instruction,
+        Math.round(Date.now() / 1000) + 60,
+        nonce
+      );
+
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(attestation, instruction)
+      );
+
+      const loan = await borrower.program.account.loan.fetch(borrower.loan);
+      assert.deepEqual(loan.state, { active: {} });
+    });
+
+    it("Will not accept an attestation nonce twice", async () => {
+      const nonce = BigInt(Date.now());
+      const expiry = Math.round(Date.now() / 1000) + 60;
+
+      for (const expectUsed of [false, true]) {
+        const borrower = await helpers.askLoan(connection, {
+          amount: anchor.web3.LAMPORTS_PER_SOL / 100,
+          basisPoints: 500,
+          duration: 86_400,
+        });
+        const { instruction, provider } = await giveLoanInstruction(
+          borrower,
+          nonce
+        );
+        const attestation = await helpers.attest(instruction, expiry, nonce);
+
+        try {
+          await provider.sendAndConfirm(
+            new anchor.web3.Transaction().add(attestation, instruction)
+          );
+          assert.ok(!expectUsed, "Used attestation was accepted!");
+        } catch (err) {
+          assert.ok(expectUsed);
+          assert.ok(err.logs.join("\n").includes("AttestationUsed"));
+        }
+      }
+    });
+
+    it("Will not accept an expired attestation", async () => {
+      const borrower = await helpers.askLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL / 100,
+        basisPoints: 500,
+        duration: 86_400,
+      });
+      const nonce = BigInt(Date.now());
+      const { instruction, provider } = await giveLoanInstruction(
+        borrower,
+        nonce
+      );
+      const attestation = await helpers.attest(
+        instruction,
+        Math.round(Date.now() / 1000) - 60,
+        nonce
+      );
+
+      try {
+        await provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(attestation, instruction)
+        );
+        assert.fail("Expired attestation was accepted!");
+      } catch (err) {
+        assert.ok(err.logs.join("\n").includes("AttestationExpired"));
+      }
+    });
+
+    it("Gives a loan in a permissionless collection without the co-signer", async () => {
+      const signer = await helpers.getSigner();
+      const authority = await helpers.getAuthority();
+      const borrower = await helpers.askLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL / 100,
+        basisPoints: 500,
+        duration: 86_400,
+      });
+      const loan = await borrower.program.account.loan.fetch(borrower.loan);
+      const collection = await borrower.program.account.collection.fetch(
+        loan.collection
+      );
+
+      await helpers
+        .getProgram(helpers.getProvider(connection, authority))
+        .methods.updateCollection({
+          ...collection.config,
+          permissionless: true,
+        })
+        .accounts({
+          signer: signer.publicKey,
+          globalConfig: helpers.findGlobalConfigAddress(),
+          authority: authority.publicKey,
+          collection: loan.collection,
+        })
+        .signers([signer])
+        .rpc();
+
+      const { instruction, provider } = await giveLoanInstruction(
+        borrower,
+        null
+      );
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(instruction)
+      );
+
+      const activeLoan = await borrower.program.account.loan.fetch(
+        borrower.loan
+      );
+      assert.deepEqual(activeLoan.state, { active: {} });
+    });
+  });
+
+  describe("Auctions", () => {
+    it("Activates the loan with the lowest bid", async () => {
+      const borrower = await helpers.askLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 1_000,
+        duration: 86_400,
+        auctionEndsAt: Math.round(Date.now() / 1000) + 6,
+      });
+
+      const first = await helpers.bidLoan(connection, borrower, 800);
+      const firstBalanceBefore = await connection.getBalance(
+        first.keypair.publicKey
+      );
+      const second = await helpers.bidLoan(
+        connection,
+        borrower,
+        600,
+        first.keypair.publicKey
+      );
+      const firstBalanceAfter = await connection.getBalance(
+        first.keypair.publicKey
+      );
+
+      assert.equal(
+        firstBalanceAfter - firstBalanceBefore,
+        anchor.web3.LAMPORTS_PER_SOL,
+        "refund"
+      );
+
+      await helpers.wait(8);
+
+      await borrower.program.methods
+        .settleLoanAuction()
+        .accounts({
+          globalConfig: helpers.findGlobalConfigAddress(),
+          borrower: borrower.keypair.publicKey,
+          lender: second.keypair.publicKey,
+          loan: borrower.loan,
+          escrowPaymentAccount: helpers.findLoanAuctionVaultAddress(
+            borrower.loan
+          ),
+          treasury: helpers.findTreasuryAddress(),
+          mint: borrower.mint,
+        })
+        .rpc();
+
+      const loan = await borrower.program.account.loan.fetch(borrower.loan);
+
+      assert.deepEqual(loan.state, { active: {} });
+      assert.ok(loan.lender.equals(second.keypair.publicKey), "lender");
+      assert.equal(loan.basisPoints, 600, "basisPoints");
+    });
+  });
+
+  describe("Listing expiry", () => {
+    let borrower: helpers.LoanBorrower;
+
+    it("Will not allow an expired listing to be given", async () => {
+      borrower = await helpers.askLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL / 100,
+        basisPoints: 500,
+        duration: 86_400,
+        listingExpiresAt: Math.round(Date.now() / 1000) + 2,
+      });
+
+      await helpers.wait(4);
+
+      try {
+        await helpers.giveLoan(connection, borrower);
+        assert.fail("Expired listing was given!");
+      } catch (err) {
+        assert(err instanceof anchor.AnchorError);
+        assert.equal(err.error.errorCode.code, "ListingExpired");
+      }
+    });
+
+    it("Allows anyone to close an expired listing", async () => {
+      const keypair = anchor.web3.Keypair.generate();
+      const provider = helpers.getProvider(connection, keypair);
+      const program = helpers.getProgram(provider);
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+
+      const loanAccount = await program.account.loan.fetch(borrower.loan);
+      assert.equal(
+        loanAccount.permissionless,
+        false,
+        "The close must not rely on a permissionless loan"
+      );
+
+      await program.methods
+        .closeExpiredLoan()
+        .accounts({
+          payer: keypair.publicKey,
+          borrower: borrower.keypair.publicKey,
+          depositTokenAccount: borrower.depositTokenAccount,
+          depositTokenRecord: borrower.tokenRecord,
+          loan: borrower.loan,
+          tokenManager: borrower.tokenManager,
+          mint: borrower.mint,
+          metadata: borrower.metadata,
+          edition: borrower.edition,
+          authorizationRules: null,
+          authorizationRulesProgram: AUTHORIZATION_RULES_PROGRAM_ID,
+          metadataProgram: METADATA_PROGRAM_ID,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      const loanAccountInfo = await connection.getAccountInfo(borrower.loan);
+      const borrowerTokenAccount = await splToken.getAccount(
+        connection,
+        borrower.depositTokenAccount
+      );
+      assert.equal(loanAccountInfo, null, "Loan account should be null");
+      assert.equal(borrowerTokenAccount.isFrozen, false, "isFrozen");
+    });
+  });
+
   describe("Loan repayments", () => {
     let borrower: Awaited<ReturnType<typeof helpers.askLoan>>;
     let lender: Awaited<ReturnType<typeof helpers.giveLoan>>;
@@ -466,6 +1295,8 @@ describe("Loans", () => {
       assert.equal(loan.mint.toBase58(), borrower.mint.toBase58());
       assert.equal(borrowerTokenAccount.amount, BigInt(1));
       assert.deepEqual(loan.state, { listed: {} });
+      assert.equal(loan.sequence.toNumber(), 1);
+      assert.equal(tokenManager.sequence.toNumber(), 1);
     });
 
     it("Prevents reinitialization", async () => {
@@ -476,12 +1307,27 @@ describe("Loans", () => {
         const signer = await helpers.getSigner();
 
         await borrower.program.methods
-          .askLoan(new anchor.BN(amount), basisPoints, new anchor.BN(1))
+          .askLoan(
+            new anchor.BN(amount),
+            basisPoints,
+            new anchor.BN(1),
+            null,
+            [],
+            null,
+            []
+          )
           .accounts({
             signer: signer.publicKey,
+            attestationReceipt: null,
+            globalConfig: helpers.findGlobalConfigAddress(),
             borrower: borrower.keypair.publicKey,
-            loan: borrower.loan,
+            loan: helpers.findLoanAddress(
+              borrower.mint,
+              borrower.keypair.publicKey,
+              2
+            ),
             collection: borrower.collection,
+            denylist: helpers.findDenylistAddress(borrower.collection),
             tokenManager: borrower.tokenManager,
             depositTokenAccount: borrower.depositTokenAccount,
             tokenRecord: borrower.tokenRecord,
@@ -500,18 +1346,15 @@ describe("Loans", () => {
           .rpc();
         assert.fail();
       } catch (error) {
-        assert.ok(error.toString().includes("custom program error: 0x0"));
+        assert.ok(error.toString().includes("InvalidState"));
       }
     });
 
     it("Allows unactive loans to be closed", async () => {
       try {
-        const signer = await helpers.getSigner();
-
         await borrower.program.methods
           .closeLoan()
           .accounts({
-            signer: signer.publicKey,
             borrower: borrower.keypair.publicKey,
             depositTokenAccount: borrower.depositTokenAccount,
             depositTokenRecord: borrower.tokenRecord,
@@ -527,7 +1370,6 @@ describe("Loans", () => {
             sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
             systemProgram: anchor.web3.SystemProgram.programId,
           })
-          .signers([signer])
           .rpc();
       } catch (error) {
         console.log(error.logs);
@@ -549,13 +1391,23 @@ describe("Loans", () => {
 
       const signer = await helpers.getSigner();
 
+      // The token manager outlives the closed listing, so the next loan follows on in sequence
+      borrower.loan = helpers.findLoanAddress(
+        borrower.mint,
+        borrower.keypair.publicKey,
+        2
+      );
+
       await borrower.program.methods
-        .askLoan(amount, basisPoints, duration)
+        .askLoan(amount, basisPoints, duration, null, [], null, [])
         .accounts({
           signer: signer.publicKey,
+          attestationReceipt: null,
+          globalConfig: helpers.findGlobalConfigAddress(),
           loan: borrower.loan,
           borrower: borrower.keypair.publicKey,
           collection: borrower.collection,
+          denylist: helpers.findDenylistAddress(borrower.collection),
           tokenManager: borrower.tokenManager,
           depositTokenAccount: borrower.depositTokenAccount,
           tokenRecord: borrower.tokenRecord,
@@ -589,6 +1441,7 @@ describe("Loans", () => {
         loan.borrower.toBase58(),
         borrower.keypair.publicKey.toBase58()
       );
+      assert.equal(loan.sequence.toNumber(), 2);
     });
 
     it("Allows loans to be given", async () => {
@@ -636,16 +1489,14 @@ describe("Loans", () => {
       );
 
       try {
-        const signer = await helpers.getSigner();
-
         await lender.program.methods
           .repossess()
           .accounts({
-            signer: signer.publicKey,
             borrower: borrower.keypair.publicKey,
             depositTokenAccount: borrower.depositTokenAccount,
             depositTokenRecord: borrower.tokenRecord,
             lender: lender.keypair.publicKey,
+            lenderRisk: helpers.findLenderRiskAddress(lender.keypair.publicKey),
             escrowTokenAccount,
             escrowTokenRecord,
             loan: borrower.loan,
@@ -663,7 +1514,6 @@ describe("Loans", () => {
             clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           })
-          .signers([signer])
           .rpc();
 
         assert.ok(false);
@@ -673,7 +1523,6 @@ describe("Loans", () => {
     });
 
     it("Allows loans to be repaid", async () => {
-      const signer = await helpers.getSigner();
       const lenderPreRepaymentBalance = await connection.getBalance(
         lender.keypair.publicKey
       );
@@ -687,10 +1536,13 @@ describe("Loans", () => {
       const signature = await borrower.program.methods
         .repayLoan(loan.amount)
         .accounts({
-          signer: signer.publicKey,
+          globalConfig: helpers.findGlobalConfigAddress(),
           loan: borrower.loan,
           borrower: borrower.keypair.publicKey,
           lender: lender.keypair.publicKey,
+          lenderRisk: helpers.findLenderRiskAddress(lender.keypair.publicKey),
+          collection: borrower.collection,
+          treasury: helpers.findTreasuryAddress(),
           mint: borrower.mint,
           metadata: borrower.metadata,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -703,7 +1555,6 @@ describe("Loans", () => {
             isWritable: true,
           }))
         )
-        .signers([signer])
         .rpc({ commitment: "confirmed" });
 
       loan = await borrower.program.account.loan.fetch(borrower.loan);
@@ -732,12 +1583,9 @@ describe("Loans", () => {
     });
 
     it("Allows loans to be closed after repayment", async () => {
-      const signer = await helpers.getSigner();
-
       await borrower.program.methods
         .closeLoan()
         .accounts({
-          signer: signer.publicKey,
           borrower: borrower.keypair.publicKey,
           depositTokenAccount: borrower.depositTokenAccount,
           depositTokenRecord: borrower.tokenRecord,
@@ -753,19 +1601,13 @@ describe("Loans", () => {
           sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
-        .signers([signer])
         .rpc();
 
-      try {
-        await borrower.program.account.tokenManager.fetch(
-          borrower.tokenManager
-        );
-      } catch (error) {
-        assert.ok(
-          error.toString(),
-          `Account does not exist ${borrower.tokenManager.toBase58()}`
-        );
-      }
+      const tokenManager = await borrower.program.account.tokenManager.fetch(
+        borrower.tokenManager
+      );
+      assert.equal(tokenManager.authority, null);
+      assert.equal(tokenManager.sequence.toNumber(), 2);
       const borrowerTokenAccount = await splToken.getAccount(
         connection,
         borrower.depositTokenAccount
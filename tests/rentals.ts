@@ -73,6 +73,7 @@ const connection = new anchor.web3.Connection(
 //           .takeRental(1)
 //           .accounts({
 //             signer: signer.publicKey,
+//             globalConfig: helpers.findGlobalConfigAddress(),
 //             borrower: newKeypair.publicKey,
 //             lender: lender.keypair.publicKey,
 //             rental: lender.rental,
@@ -131,6 +132,7 @@ const connection = new anchor.web3.Connection(
 //         .takeRental(days)
 //         .accounts({
 //           signer: signer.publicKey,
+//           globalConfig: helpers.findGlobalConfigAddress(),
 //           borrower: privateBorrower.publicKey,
 //           lender: lender.keypair.publicKey,
 //           rental: lender.rental,
@@ -208,6 +210,7 @@ const connection = new anchor.web3.Connection(
 //           .recoverRental()
 //           .accounts({
 //             signer: signer.publicKey,
+//             globalConfig: helpers.findGlobalConfigAddress(),
 //             borrower: privateBorrower.publicKey,
 //             lender: lender.keypair.publicKey,
 //             rental: lender.rental,
@@ -334,6 +337,7 @@ const connection = new anchor.web3.Connection(
 //         .initRental({ amount, expiry, borrower: null })
 //         .accounts({
 //           signer: signer.publicKey,
+//           globalConfig: helpers.findGlobalConfigAddress(),
 //           rental: rentalAddress,
 //           collection: borrower.collection,
 //           tokenManager: tokenManagerAddress,
@@ -397,6 +401,7 @@ const connection = new anchor.web3.Connection(
 //           .takeRental(2)
 //           .accounts({
 //             signer: signer.publicKey,
+//             globalConfig: helpers.findGlobalConfigAddress(),
 //             borrower: thirdPartyKeypair.publicKey,
 //             lender: borrower.keypair.publicKey,
 //             rental: rentalAddress,
@@ -461,6 +466,7 @@ const connection = new anchor.web3.Connection(
 //         .repayLoan()
 //         .accounts({
 //           signer: signer.publicKey,
+//           globalConfig: helpers.findGlobalConfigAddress(),
 //           loan: borrower.loan,
 //           tokenManager: borrower.tokenManager,
 //           borrower: borrower.keypair.publicKey,
@@ -551,6 +557,7 @@ const connection = new anchor.web3.Connection(
 //         .initRental({ amount, expiry, borrower: null })
 //         .accounts({
 //           signer: signer.publicKey,
+//           globalConfig: helpers.findGlobalConfigAddress(),
 //           rental: rentalAddress,
 //           collection: borrower.collection,
 //           tokenManager: tokenManagerAddress,
@@ -614,6 +621,7 @@ const connection = new anchor.web3.Connection(
 //           .takeRental(2)
 //           .accounts({
 //             signer: signer.publicKey,
+//             globalConfig: helpers.findGlobalConfigAddress(),
 //             borrower: thirdPartyKeypair.publicKey,
 //             lender: borrower.keypair.publicKey,
 //             rental: rentalAddress,
@@ -698,6 +706,7 @@ const connection = new anchor.web3.Connection(
 //       try {
 //         const accounts = {
 //           signer: signer.publicKey,
+//           globalConfig: helpers.findGlobalConfigAddress(),
 //           rental: rentalAddress,
 //           rentalEscrow: rentalEscrowAddress,
 //           borrower: borrower.keypair.publicKey,
@@ -796,6 +805,7 @@ const connection = new anchor.web3.Connection(
 //         .initRental({ amount, expiry, borrower: null })
 //         .accounts({
 //           signer: signer.publicKey,
+//           globalConfig: helpers.findGlobalConfigAddress(),
 //           rental: rentalAddress,
 //           collection: borrower.collection,
 //           tokenManager: tokenManagerAddress,
@@ -853,6 +863,7 @@ const connection = new anchor.web3.Connection(
 //           .repossessWithRental()
 //           .accounts({
 //             signer: signer.publicKey,
+//             globalConfig: helpers.findGlobalConfigAddress(),
 //             rental: rentalAddress,
 //             rentalEscrow: rentalEscrowAddress,
 //             borrower: borrower.keypair.publicKey,
@@ -939,6 +950,7 @@ const connection = new anchor.web3.Connection(
 //         .initRental(rentalOptions)
 //         .accounts({
 //           signer: signer.publicKey,
+//           globalConfig: helpers.findGlobalConfigAddress(),
 //           rental: rentalAddress,
 //           collection: seller.collection,
 //           tokenManager: seller.tokenManager,
@@ -1023,6 +1035,7 @@ const connection = new anchor.web3.Connection(
 //           .takeRental(2)
 //           .accounts({
 //             signer: signer.publicKey,
+//             globalConfig: helpers.findGlobalConfigAddress(),
 //             rentalTokenAccount,
 //             borrower: thirdPartyKeypair.publicKey,
 //             lender: seller.keypair.publicKey,
@@ -1119,6 +1132,7 @@ const connection = new anchor.web3.Connection(
 //           .exerciseCallOptionWithRental()
 //           .accounts({
 //             signer: signer.publicKey,
+//             globalConfig: helpers.findGlobalConfigAddress(),
 //             seller: seller.keypair.publicKey,
 //             buyer: buyer.keypair.publicKey,
 //             callOption: seller.callOption,
@@ -1285,6 +1299,7 @@ const connection = new anchor.web3.Connection(
 //           .askCallOption(amount, strikePrice, expiry)
 //           .accounts({
 //             signer: signer.publicKey,
+//             globalConfig: helpers.findGlobalConfigAddress(),
 //             tokenManager,
 //             callOption: callOptionAddress,
 //             collection: lender.collection,
@@ -1330,6 +1345,7 @@ const connection = new anchor.web3.Connection(
 //         .askCallOption(amount, strikePrice, expiry)
 //         .accounts({
 //           signer: signer.publicKey,
+//           globalConfig: helpers.findGlobalConfigAddress(),
 //           tokenManager,
 //           callOption: callOptionAddress,
 //           collection: lender.collection,
@@ -0,0 +1,43 @@
+require("dotenv").config();
+
+import * as anchor from "@project-serum/anchor";
+import * as helpers from "./helpers";
+
+// Every instruction reads the admin and co-signers from the global config,
+// which only the program's upgrade authority can create
+before(async () => {
+  const provider = anchor.AnchorProvider.env();
+  const program = helpers.getProgram(provider);
+  const globalConfigPda = helpers.findGlobalConfigAddress();
+
+  const globalConfigAccount = await provider.connection.getAccountInfo(
+    globalConfigPda
+  );
+
+  if (globalConfigAccount !== null) {
+    return;
+  }
+
+  const signer = await helpers.getSigner();
+  const authority = await helpers.getAuthority();
+
+  // Fees are left at zero so balances in the other suites are unaffected
+  await program.methods
+    .initGlobalConfig(
+      authority.publicKey,
+      [signer.publicKey],
+      {
+        feeBasisPoints: 0,
+        originationFeeBasisPoints: 0,
+        referralShareBasisPoints: 0,
+      },
+      { loansEnabled: true, optionsEnabled: true }
+    )
+    .accounts({
+      payer: provider.wallet.publicKey,
+      globalConfig: globalConfigPda,
+      program: program.programId,
+      programData: helpers.findProgramDataAddress(),
+    })
+    .rpc();
+});
@@ -0,0 +1,114 @@
+require("dotenv").config();
+
+import assert from "assert";
+import * as anchor from "@project-serum/anchor";
+import * as helpers from "./helpers";
+import { OndaListings } from "../target/types/onda_listings";
+
+// Configure the client to use the local cluster.
+const connection = new anchor.web3.Connection(
+  "http://127.0.0.1:8899",
+  anchor.AnchorProvider.defaultOptions().preflightCommitment
+);
+
+describe("Treasury", async () => {
+  let authority: anchor.web3.Keypair;
+  let provider: anchor.AnchorProvider;
+  let program: anchor.Program<OndaListings>;
+
+  const treasuryPda = helpers.findTreasuryAddress();
+
+  it("Initializes the treasury", async () => {
+    const signer = await helpers.getSigner();
+    authority = await helpers.getAuthority();
+    provider = helpers.getProvider(connection, authority);
+    program = helpers.getProgram(provider);
+    await helpers.requestAirdrop(connection, authority.publicKey);
+
+    await program.methods
+      .initTreasury()
+      .accounts({
+        signer: signer.publicKey,
+        globalConfig: helpers.findGlobalConfigAddress(),
+        authority: authority.publicKey,
+        treasury: treasuryPda,
+      })
+      .signers([signer])
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    assert.ok(treasury.bump);
+  });
+
+  it("Will not allow fees above 100%", async () => {
+    const signer = await helpers.getSigner();
+
+    try {
+      await program.methods
+        .updateGlobalConfig(
+          [signer.publicKey],
+          {
+            feeBasisPoints: 10_001,
+            originationFeeBasisPoints: 0,
+            referralShareBasisPoints: 0,
+          },
+          { loansEnabled: true, optionsEnabled: true }
+        )
+        .accounts({
+          globalConfig: helpers.findGlobalConfigAddress(),
+          admin: authority.publicKey,
+        })
+        .rpc();
+      assert.fail();
+    } catch (err) {
+      assert.ok(err.toString().includes("InvalidBasisPoints"));
+    }
+  });
+
+  it("Allows the admin to withdraw fees", async () => {
+    const signer = await helpers.getSigner();
+    const amount = 1_000_000;
+
+    await helpers.requestAirdrop(connection, treasuryPda);
+    const preWithdrawalBalance = await connection.getBalance(treasuryPda);
+
+    await program.methods
+      .withdrawFromTreasury(new anchor.BN(amount))
+      .accounts({
+        signer: signer.publicKey,
+        globalConfig: helpers.findGlobalConfigAddress(),
+        authority: authority.publicKey,
+        treasury: treasuryPda,
+      })
+      .signers([signer])
+      .rpc();
+
+    const postWithdrawalBalance = await connection.getBalance(treasuryPda);
+    assert.equal(preWithdrawalBalance - postWithdrawalBalance, amount);
+  });
+
+  it("Will not allow anyone else to withdraw fees", async () => {
+    const signer = await helpers.getSigner();
+    const keypair = anchor.web3.Keypair.generate();
+    const program = helpers.getProgram(
+      helpers.getProvider(connection, keypair)
+    );
+    await helpers.requestAirdrop(connection, keypair.publicKey);
+
+    try {
+      await program.methods
+        .withdrawFromTreasury(new anchor.BN(1))
+        .accounts({
+          signer: signer.publicKey,
+          globalConfig: helpers.findGlobalConfigAddress(),
+          authority: keypair.publicKey,
+          treasury: treasuryPda,
+        })
+        .signers([signer])
+        .rpc();
+      assert.fail();
+    } catch (err) {
+      assert.ok(err.toString().includes("Unauthorized"));
+    }
+  });
+});